@@ -1,4 +1,15 @@
-use std::{env, path::Path, process::Command};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::Engine;
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
 
 fn main() {
     let out_dir = env::var_os("OUT_DIR").unwrap();
@@ -7,6 +18,8 @@ fn main() {
     let manifest_dir = env::var_os("CARGO_MANIFEST_DIR").unwrap();
     let manifest_path = Path::new(&manifest_dir);
 
+    rerun_if_inputs_change(manifest_path);
+
     Command::new("cp")
         .arg("-R")
         .arg(manifest_path.join("static"))
@@ -39,4 +52,1038 @@ fn main() {
         "cargo::warning=static-embedded assets: {:?}",
         String::from_utf8(assets)
     );
+
+    write_post_history(manifest_path, out_path);
+    write_post_stats(manifest_path, out_path);
+    write_series(manifest_path, out_path);
+
+    let tags = read_tags(manifest_path);
+    write_tags(&tags, out_path);
+    write_projects(&tags, manifest_path, out_path);
+    write_resume(&tags, manifest_path, out_path);
+    write_homepage(manifest_path, out_path);
+
+    write_github_metadata(manifest_path, out_path);
+    write_build_info(manifest_path, out_path);
+    write_script_hashes(out_path);
+}
+
+/// Tell cargo what this script reads, so that it reruns when any of it changes:
+/// the site's content and styles, and git's current commit, which the post
+/// history and build info come from. (Without this, cargo reruns it when any
+/// file in the package changes, which excludes .git.)
+fn rerun_if_inputs_change(manifest_path: &Path) {
+    for input in [
+        "build.rs",
+        "input.css",
+        "src",
+        "static",
+        "posts",
+        "projects",
+        "tags.toml",
+        "projects.toml",
+        "resume.toml",
+        "homepage.toml",
+        "series.toml",
+    ] {
+        println!("cargo::rerun-if-changed={input}");
+    }
+    // a missing input counts as changed on every build, and the snapshot is
+    // only required (and so only read) when the `github-metadata` feature is on
+    if manifest_path.join("github.json").exists() {
+        println!("cargo::rerun-if-changed=github.json");
+    }
+
+    let git_dir = Command::new("git")
+        .args(["rev-parse", "--absolute-git-dir"])
+        .current_dir(manifest_path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()));
+    if let Some(git_dir) = git_dir {
+        for input in ["HEAD", "refs", "packed-refs"] {
+            if git_dir.join(input).exists() {
+                println!("cargo::rerun-if-changed={}", git_dir.join(input).display());
+            }
+        }
+    }
+}
+
+/// markdown files in /posts, sorted by file name
+fn post_files(manifest_path: &Path) -> Vec<PathBuf> {
+    let mut post_files = fs::read_dir(manifest_path.join("posts"))
+        .expect("failed to read /posts")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+        .collect::<Vec<_>>();
+    post_files.sort();
+    post_files
+}
+
+fn post_id(post_file: &Path) -> String {
+    post_file.file_stem().unwrap().to_string_lossy().to_string()
+}
+
+/// Generate `$OUT_DIR/post_history.rs`, which holds the git revisions of each
+/// file in /posts, keyed by post id (the file stem).
+///
+/// If git or the repository is unavailable (e.g. building from a tarball), every
+/// post simply has no revisions.
+fn write_post_history(manifest_path: &Path, out_path: &Path) {
+    let mut generated = String::from("static POST_HISTORY: &[(&str, &[Revision])] = &[\n");
+
+    for post_file in post_files(manifest_path) {
+        let id = post_id(&post_file);
+
+        // one entry per commit: <nul> <sha> <tab> <yyyy-mm-dd> <tab> <subject>,
+        // then the file's path at that commit (relative to the repository) on
+        // its own line
+        let log = Command::new("git")
+            .arg("log")
+            .arg("--follow")
+            .arg("--name-only")
+            .arg("--format=%x00%H%x09%as%x09%s")
+            .arg("--")
+            .arg(&post_file)
+            .current_dir(manifest_path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default();
+
+        generated.push_str(&format!("    ({id:?}, &[\n"));
+        for entry in log.split('\0').filter(|entry| !entry.is_empty()) {
+            let mut lines = entry.lines().filter(|line| !line.is_empty());
+            let (Some(line), Some(path)) = (lines.next(), lines.next()) else {
+                continue;
+            };
+            let mut fields = line.splitn(3, '\t');
+            let (Some(sha), Some(date), Some(summary)) = (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let file_anchor = format!("diff-{:x}", Sha256::digest(path));
+            let mut ymd = date.split('-').map(|n| n.parse::<u32>().unwrap_or_default());
+            let (year, month, day) = (
+                ymd.next().unwrap_or_default(),
+                ymd.next().unwrap_or_default(),
+                ymd.next().unwrap_or_default(),
+            );
+            generated.push_str(&format!(
+                "        Revision {{ sha: {sha:?}, date: NaiveDate::from_ymd_opt({year}, {month}, {day}).unwrap(), summary: {summary:?}, file_anchor: {file_anchor:?} }},\n"
+            ));
+        }
+        generated.push_str("    ]),\n");
+    }
+
+    generated.push_str("];\n");
+
+    fs::write(out_path.join("post_history.rs"), generated)
+        .expect("failed to write post_history.rs");
+}
+
+/// Generate `$OUT_DIR/post_stats.rs`, which holds the word count of each file in
+/// /posts, keyed by post id. Code blocks and footnote definitions don't count.
+fn write_post_stats(manifest_path: &Path, out_path: &Path) {
+    let mut generated = String::from("static POST_STATS: &[(&str, PostStats)] = &[\n");
+
+    for post_file in post_files(manifest_path) {
+        let markdown = fs::read_to_string(&post_file).expect("failed to read post");
+
+        // the prose, with blocks and line breaks separated by spaces. It's
+        // buffered rather than counted per event because smart punctuation
+        // and inline markup split words across events: "don't" and
+        // "un*believ*able" are one word each
+        let mut prose = String::new();
+        let mut skip_depth = 0;
+        for event in Parser::new_ext(&markdown, Options::all()) {
+            match event {
+                Event::Start(Tag::CodeBlock(_) | Tag::FootnoteDefinition(_)) => skip_depth += 1,
+                Event::End(TagEnd::CodeBlock | TagEnd::FootnoteDefinition) => skip_depth -= 1,
+                Event::Text(text) | Event::Code(text) if skip_depth == 0 => prose.push_str(&text),
+                // inline markup doesn't separate words, but block boundaries do
+                Event::Start(
+                    Tag::Emphasis
+                    | Tag::Strong
+                    | Tag::Strikethrough
+                    | Tag::Superscript
+                    | Tag::Subscript
+                    | Tag::Link { .. }
+                    | Tag::Image { .. },
+                )
+                | Event::End(
+                    TagEnd::Emphasis
+                    | TagEnd::Strong
+                    | TagEnd::Strikethrough
+                    | TagEnd::Superscript
+                    | TagEnd::Subscript
+                    | TagEnd::Link
+                    | TagEnd::Image,
+                ) => {}
+                Event::Start(_) | Event::End(_) | Event::SoftBreak | Event::HardBreak => {
+                    prose.push(' ')
+                }
+                _ => {}
+            }
+        }
+        let word_count = prose.split_whitespace().count();
+
+        generated.push_str(&format!(
+            "    ({:?}, PostStats {{ word_count: {word_count} }}),\n",
+            post_id(&post_file)
+        ));
+    }
+
+    generated.push_str("];\n");
+
+    fs::write(out_path.join("post_stats.rs"), generated).expect("failed to write post_stats.rs");
+}
+
+/// category slugs which would collide with other routes under /projects
+const RESERVED_CATEGORY_SLUGS: &[&str] = &["timeline"];
+
+/// snake_case names of `ProjectStatus` variants, as used in projects.toml,
+/// with the variant they refer to
+const PROJECT_STATUSES: &[(&str, &str)] = &[
+    ("active", "Active"),
+    ("maintained", "Maintained"),
+    ("experiment", "Experiment"),
+    ("archived", "Archived"),
+];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectsFile {
+    category: Vec<CategoryEntry>,
+    project: Vec<ProjectEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CategoryEntry {
+    slug: String,
+    title: String,
+    description: String,
+    order: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectEntry {
+    id: String,
+    title: String,
+    description: String,
+    tech_stack: Vec<String>,
+    github_url: Option<String>,
+    try_it_url: Option<String>,
+    writeup: Option<String>,
+    #[serde(default)]
+    screenshots: Vec<ScreenshotEntry>,
+    category: String,
+    status: Option<String>,
+    started: Option<toml::value::Datetime>,
+    ended: Option<toml::value::Datetime>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScreenshotEntry {
+    src: String,
+    alt: String,
+}
+
+/// tags.toml, as tag key to display name
+fn read_tags(manifest_path: &Path) -> BTreeMap<String, String> {
+    let tags_path = manifest_path.join("tags.toml");
+    let tags = fs::read_to_string(&tags_path).expect("failed to read tags.toml");
+    toml::from_str(&tags).unwrap_or_else(|e| panic!("invalid tags.toml: {e}"))
+}
+
+/// the `tag` module constant for a tags.toml key, e.g. `fly-io` -> `FLY_IO`
+fn tag_const(key: &str) -> String {
+    key.to_uppercase().replace('-', "_")
+}
+
+/// Generate `$OUT_DIR/tags.rs`, a constant for each tag in tags.toml.
+fn write_tags(tags: &BTreeMap<String, String>, out_path: &Path) {
+    let mut generated = String::new();
+    for (key, display) in tags {
+        generated.push_str(&format!(
+            "pub const {}: Tag = {display:?};\n",
+            tag_const(key)
+        ));
+    }
+    fs::write(out_path.join("tags.rs"), generated).expect("failed to write tags.rs");
+}
+
+/// Generate `$OUT_DIR/project_categories.rs` and `$OUT_DIR/projects.rs`, the
+/// `Vec<ProjectCategory>` and `Vec<Project>` expressions described by
+/// projects.toml. Every problem in the file is reported at once, failing the build.
+fn write_projects(tags: &BTreeMap<String, String>, manifest_path: &Path, out_path: &Path) {
+    let projects_path = manifest_path.join("projects.toml");
+    let projects = fs::read_to_string(&projects_path).expect("failed to read projects.toml");
+    let mut projects: ProjectsFile =
+        toml::from_str(&projects).unwrap_or_else(|e| panic!("invalid projects.toml: {e}"));
+
+    let mut errors = vec![];
+
+    projects.category.sort_by_key(|category| category.order);
+    if projects.category.is_empty() {
+        errors.push("there must be at least one category".to_string());
+    }
+
+    let mut slugs = HashSet::new();
+    let mut orders = HashSet::new();
+    let mut generated_categories = String::from("vec![\n");
+    for category in &projects.category {
+        let slug = &category.slug;
+        if !slugs.insert(slug) {
+            errors.push(format!("duplicate category slug `{slug}`"));
+        }
+        if !orders.insert(category.order) {
+            errors.push(format!(
+                "category `{slug}` has the same order as another category"
+            ));
+        }
+        let valid_slug = !slug.is_empty()
+            && slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !valid_slug {
+            errors.push(format!(
+                "category slug `{slug}` may only contain a-z, 0-9, _ and -"
+            ));
+        }
+        if RESERVED_CATEGORY_SLUGS.contains(&slug.as_str()) {
+            errors.push(format!("category slug `{slug}` is reserved"));
+        }
+        generated_categories.push_str(&format!(
+            "    ProjectCategory {{ slug: {slug:?}, title: {:?}, description: {:?} }},\n",
+            category.title, category.description
+        ));
+    }
+    generated_categories.push_str("]\n");
+
+    let mut ids = HashSet::new();
+    let mut generated = String::from("vec![\n");
+
+    for project in &projects.project {
+        let id = &project.id;
+
+        if !ids.insert(id) {
+            errors.push(format!("duplicate project id `{id}`"));
+        }
+
+        let category = match projects
+            .category
+            .iter()
+            .position(|category| category.slug == project.category)
+        {
+            Some(index) => index,
+            None => {
+                errors.push(format!(
+                    "project `{id}` has unknown category `{}`; add it as a [[category]]",
+                    project.category
+                ));
+                0
+            }
+        };
+        let status = project.status.as_ref().map(|status| {
+            enum_variant(
+                PROJECT_STATUSES,
+                status,
+                &format!("project `{id}` has unknown status"),
+                &mut errors,
+            )
+        });
+
+        let (started, ended) = date_range(
+            project.started,
+            project.ended,
+            &format!("project `{id}`"),
+            &mut errors,
+        );
+
+        for tech in &project.tech_stack {
+            if !tags.contains_key(tech) {
+                errors.push(format!(
+                    "project `{id}` has unknown tag `{tech}`; add it to tags.toml"
+                ));
+            }
+        }
+
+        for url in [&project.github_url, &project.try_it_url]
+            .into_iter()
+            .flatten()
+        {
+            if !is_http_url(url) {
+                errors.push(format!("project `{id}` has malformed url `{url}`"));
+            }
+        }
+
+        let writeup = project.writeup.as_ref().map(|writeup| {
+            let writeup_path = manifest_path.join(writeup);
+            if !writeup_path.is_file() {
+                errors.push(format!("project `{id}` has missing writeup `{writeup}`"));
+            }
+            writeup_path
+        });
+
+        for screenshot in &project.screenshots {
+            let exists = screenshot
+                .src
+                .strip_prefix("/static/")
+                .is_some_and(|file| manifest_path.join("static").join(file).is_file());
+            if !exists {
+                errors.push(format!(
+                    "project `{id}` has screenshot `{}` which is not in /static",
+                    screenshot.src
+                ));
+            }
+        }
+
+        let optional = |s: &Option<String>| match s {
+            Some(s) => format!("Some({s:?})"),
+            None => "None".to_string(),
+        };
+
+        generated.push_str(&format!(
+            "    Project {{
+        id: {id:?},
+        title: {:?},
+        description: {:?},
+        tech_stack: vec![{}],
+        github_url: {},
+        try_it_url: {},
+        writeup: {},
+        screenshots: vec![{}],
+        category: &PROJECT_CATEGORIES[{category}],
+        status: {},
+        started: {},
+        ended: {},
+    }},\n",
+            project.title,
+            project.description,
+            project
+                .tech_stack
+                .iter()
+                .map(|tech| format!("tag::{}", tag_const(tech)))
+                .collect::<Vec<_>>()
+                .join(", "),
+            optional(&project.github_url),
+            optional(&project.try_it_url),
+            match writeup {
+                Some(path) => format!("Some(include_str!({:?}))", path.to_string_lossy()),
+                None => "None".to_string(),
+            },
+            project
+                .screenshots
+                .iter()
+                .map(|s| format!("Screenshot {{ src: {:?}, alt: {:?} }}", s.src, s.alt))
+                .collect::<Vec<_>>()
+                .join(", "),
+            match status {
+                Some(status) => format!("Some(ProjectStatus::{status})"),
+                None => "None".to_string(),
+            },
+            optional_date(started),
+            optional_date(ended),
+        ));
+    }
+
+    generated.push_str("]\n");
+
+    if !errors.is_empty() {
+        panic!("invalid projects.toml:\n  - {}", errors.join("\n  - "));
+    }
+
+    fs::write(out_path.join("project_categories.rs"), generated_categories)
+        .expect("failed to write project_categories.rs");
+    fs::write(out_path.join("projects.rs"), generated).expect("failed to write projects.rs");
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ResumeFile {
+    basics: BasicsEntry,
+    #[serde(default)]
+    work: Vec<WorkEntry>,
+    #[serde(default)]
+    education: Vec<EducationEntry>,
+    #[serde(default)]
+    skills: Vec<SkillsEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BasicsEntry {
+    name: String,
+    label: String,
+    summary: Option<String>,
+    #[serde(default)]
+    profiles: Vec<ProfileEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileEntry {
+    network: String,
+    username: String,
+    url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkEntry {
+    position: String,
+    organization: Option<String>,
+    url: Option<String>,
+    summary: Option<String>,
+    #[serde(default)]
+    highlights: Vec<String>,
+    started: Option<toml::value::Datetime>,
+    ended: Option<toml::value::Datetime>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EducationEntry {
+    institution: String,
+    area: String,
+    study_type: String,
+    url: Option<String>,
+    started: Option<toml::value::Datetime>,
+    ended: Option<toml::value::Datetime>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SkillsEntry {
+    name: String,
+    tags: Vec<String>,
+}
+
+/// Generate `$OUT_DIR/resume.rs`, the `Resume` in resume.toml.
+fn write_resume(tags: &BTreeMap<String, String>, manifest_path: &Path, out_path: &Path) {
+    let resume_path = manifest_path.join("resume.toml");
+    let resume = fs::read_to_string(&resume_path).expect("failed to read resume.toml");
+    let resume: ResumeFile =
+        toml::from_str(&resume).unwrap_or_else(|e| panic!("invalid resume.toml: {e}"));
+
+    let mut errors = vec![];
+
+    let optional = |s: &Option<String>| match s {
+        Some(s) => format!("Some({s:?})"),
+        None => "None".to_string(),
+    };
+
+    let mut generated_profiles = String::new();
+    for profile in &resume.basics.profiles {
+        if !is_http_url(&profile.url) {
+            errors.push(format!(
+                "profile `{}` has malformed url `{}`",
+                profile.network, profile.url
+            ));
+        }
+        generated_profiles.push_str(&format!(
+            "        Profile {{ network: {:?}, username: {:?}, url: {:?} }},\n",
+            profile.network, profile.username, profile.url
+        ));
+    }
+
+    let mut generated_work = String::new();
+    for work in &resume.work {
+        let context = format!("work `{}`", work.position);
+        if let Some(url) = &work.url
+            && !is_http_url(url)
+        {
+            errors.push(format!("{context} has malformed url `{url}`"));
+        }
+        let (started, ended) = date_range(work.started, work.ended, &context, &mut errors);
+        generated_work.push_str(&format!(
+            "        Work {{
+            position: {:?},
+            organization: {},
+            url: {},
+            summary: {},
+            highlights: vec![{}],
+            started: {},
+            ended: {},
+        }},\n",
+            work.position,
+            optional(&work.organization),
+            optional(&work.url),
+            optional(&work.summary),
+            work.highlights
+                .iter()
+                .map(|highlight| format!("{highlight:?}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            optional_date(started),
+            optional_date(ended),
+        ));
+    }
+
+    let mut generated_education = String::new();
+    for education in &resume.education {
+        let context = format!("education `{}`", education.institution);
+        if let Some(url) = &education.url
+            && !is_http_url(url)
+        {
+            errors.push(format!("{context} has malformed url `{url}`"));
+        }
+        let (started, ended) =
+            date_range(education.started, education.ended, &context, &mut errors);
+        generated_education.push_str(&format!(
+            "        Education {{
+            institution: {:?},
+            area: {:?},
+            study_type: {:?},
+            url: {},
+            started: {},
+            ended: {},
+        }},\n",
+            education.institution,
+            education.area,
+            education.study_type,
+            optional(&education.url),
+            optional_date(started),
+            optional_date(ended),
+        ));
+    }
+
+    let mut generated_skills = String::new();
+    for skills in &resume.skills {
+        if skills.tags.is_empty() {
+            errors.push(format!("skills `{}` has no tags", skills.name));
+        }
+        for tag in &skills.tags {
+            if !tags.contains_key(tag) {
+                errors.push(format!(
+                    "skills `{}` has unknown tag `{tag}`; add it to tags.toml",
+                    skills.name
+                ));
+            }
+        }
+        generated_skills.push_str(&format!(
+            "        SkillGroup {{ name: {:?}, skills: vec![{}] }},\n",
+            skills.name,
+            skills
+                .tags
+                .iter()
+                .map(|tag| format!("tag::{}", tag_const(tag)))
+                .collect::<Vec<_>>()
+                .join(", "),
+        ));
+    }
+
+    if !errors.is_empty() {
+        panic!("invalid resume.toml:\n  - {}", errors.join("\n  - "));
+    }
+
+    let generated = format!(
+        "Resume {{
+    name: {:?},
+    label: {:?},
+    summary: {},
+    profiles: vec![
+{generated_profiles}    ],
+    work: vec![
+{generated_work}    ],
+    education: vec![
+{generated_education}    ],
+    skills: vec![
+{generated_skills}    ],
+}}\n",
+        resume.basics.name,
+        resume.basics.label,
+        optional(&resume.basics.summary),
+    );
+
+    fs::write(out_path.join("resume.rs"), generated).expect("failed to write resume.rs");
+}
+
+/// snake_case names of `Icon` variants, as used in homepage.toml, with the
+/// variant they refer to
+const ICONS: &[(&str, &str)] = &[
+    ("folder", "Folder"),
+    ("pencil", "Pencil"),
+    ("document", "Document"),
+    ("github", "GitHub"),
+    ("linkedin", "LinkedIn"),
+];
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HomepageFile {
+    name: String,
+    #[serde(default)]
+    tagline: Vec<String>,
+    headshot: Option<HeadshotEntry>,
+    #[serde(default)]
+    button: Vec<ButtonEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HeadshotEntry {
+    src: String,
+    alt: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ButtonEntry {
+    title: String,
+    href: String,
+    icon: Option<String>,
+    target: Option<String>,
+    #[serde(default)]
+    rel_me: bool,
+}
+
+/// Generate `$OUT_DIR/homepage.rs`, the `Homepage` in homepage.toml.
+fn write_homepage(manifest_path: &Path, out_path: &Path) {
+    let homepage_path = manifest_path.join("homepage.toml");
+    let homepage = fs::read_to_string(&homepage_path).expect("failed to read homepage.toml");
+    let homepage: HomepageFile =
+        toml::from_str(&homepage).unwrap_or_else(|e| panic!("invalid homepage.toml: {e}"));
+
+    let mut errors = vec![];
+
+    let optional = |s: &Option<String>| match s {
+        Some(s) => format!("Some({s:?})"),
+        None => "None".to_string(),
+    };
+
+    let headshot = match &homepage.headshot {
+        Some(headshot) => {
+            let exists = headshot
+                .src
+                .strip_prefix("/static/")
+                .is_some_and(|file| manifest_path.join("static").join(file).is_file());
+            if !exists {
+                errors.push(format!("headshot `{}` is not in /static", headshot.src));
+            }
+            format!(
+                "Some(Headshot {{ src: {:?}, alt: {:?} }})",
+                headshot.src, headshot.alt
+            )
+        }
+        None => "None".to_string(),
+    };
+
+    let mut generated_buttons = String::new();
+    for button in &homepage.button {
+        let title = &button.title;
+        if !button.href.starts_with('/') && !is_http_url(&button.href) {
+            errors.push(format!(
+                "button `{title}` has malformed href `{}`; use a /path or an http(s) url",
+                button.href
+            ));
+        }
+        if button.rel_me && !is_http_url(&button.href) {
+            errors.push(format!(
+                "button `{title}` is rel_me, but only links to other sites can be"
+            ));
+        }
+        let icon = button.icon.as_ref().map(|icon| {
+            enum_variant(
+                ICONS,
+                icon,
+                &format!("button `{title}` has unknown icon"),
+                &mut errors,
+            )
+        });
+        generated_buttons.push_str(&format!(
+            "        Link {{ href: {:?}, title: {title:?}, target: {}, icon: {}, rel_me: {} }},\n",
+            button.href,
+            optional(&button.target),
+            match icon {
+                Some(icon) => format!("Some(Icon::{icon})"),
+                None => "None".to_string(),
+            },
+            button.rel_me,
+        ));
+    }
+
+    if !errors.is_empty() {
+        panic!("invalid homepage.toml:\n  - {}", errors.join("\n  - "));
+    }
+
+    let generated = format!(
+        "Homepage {{
+    name: {:?},
+    tagline: vec![{}],
+    headshot: {headshot},
+    buttons: vec![
+{generated_buttons}    ],
+}}\n",
+        homepage.name,
+        homepage
+            .tagline
+            .iter()
+            .map(|line| format!("{line:?}"))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+
+    fs::write(out_path.join("homepage.rs"), generated).expect("failed to write homepage.rs");
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SeriesFile {
+    series: Vec<SeriesEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SeriesEntry {
+    id: String,
+    title: String,
+    description: String,
+    post_ids: Vec<String>,
+}
+
+/// Generate `$OUT_DIR/series.rs` from series.toml, failing the build if a series
+/// id is reused or a series lists a post which doesn't exist.
+fn write_series(manifest_path: &Path, out_path: &Path) {
+    let series_path = manifest_path.join("series.toml");
+    let series = fs::read_to_string(&series_path).expect("failed to read series.toml");
+    let series: SeriesFile =
+        toml::from_str(&series).unwrap_or_else(|e| panic!("invalid series.toml: {e}"));
+
+    let post_ids = post_files(manifest_path)
+        .iter()
+        .map(|post_file| post_id(post_file))
+        .collect::<HashSet<_>>();
+    let mut series_ids = HashSet::new();
+    let mut errors = vec![];
+
+    let mut generated = String::from("vec![\n");
+    for entry in &series.series {
+        let id = &entry.id;
+        if !series_ids.insert(id) {
+            errors.push(format!("series id `{id}` is used more than once"));
+        }
+        if entry.post_ids.is_empty() {
+            errors.push(format!("series `{id}` has no posts"));
+        }
+        for post in &entry.post_ids {
+            if !post_ids.contains(post) {
+                errors.push(format!("series `{id}` lists unknown post `{post}`"));
+            }
+        }
+        generated.push_str(&format!(
+            "    Series {{ id: {id:?}, title: {:?}, description: {:?}, post_ids: vec!{:?} }},\n",
+            entry.title, entry.description, entry.post_ids,
+        ));
+    }
+    generated.push_str("]\n");
+
+    if !errors.is_empty() {
+        panic!("invalid series.toml:\n  - {}", errors.join("\n  - "));
+    }
+
+    fs::write(out_path.join("series.rs"), generated).expect("failed to write series.rs");
+}
+
+/// an absolute http(s) url
+fn is_http_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https") && u.host().is_some())
+}
+
+/// validated (start, end) dates of a project or résumé entry
+fn date_range(
+    started: Option<toml::value::Datetime>,
+    ended: Option<toml::value::Datetime>,
+    context: &str,
+    errors: &mut Vec<String>,
+) -> (Option<Ymd>, Option<Ymd>) {
+    let started = started.map(|started| toml_date(started, &format!("{context} started"), errors));
+    let ended = ended.map(|ended| toml_date(ended, &format!("{context} ended"), errors));
+    if started.is_none() && ended.is_some() {
+        errors.push(format!("{context} has an end date but no start date"));
+    }
+    if let (Some(started), Some(ended)) = (&started, &ended)
+        && ended < started
+    {
+        errors.push(format!("{context} ended before it started"));
+    }
+    (started, ended)
+}
+
+/// The enum variant named by `value` in `table`, or an error listing the
+/// expected values. An empty variant is returned on error so that validation
+/// can continue.
+fn enum_variant(
+    table: &[(&str, &'static str)],
+    value: &str,
+    context: &str,
+    errors: &mut Vec<String>,
+) -> &'static str {
+    match table.iter().find(|(name, _)| *name == value) {
+        Some((_, variant)) => variant,
+        None => {
+            errors.push(format!(
+                "{context} `{value}`; expected one of {:?}",
+                table.iter().map(|(name, _)| name).collect::<Vec<_>>()
+            ));
+            ""
+        }
+    }
+}
+
+/// (year, month, day)
+type Ymd = (u16, u8, u8);
+
+/// the date of a toml datetime, which must not have a time
+fn toml_date(datetime: toml::value::Datetime, context: &str, errors: &mut Vec<String>) -> Ymd {
+    match (datetime.date, datetime.time) {
+        (Some(date), None) => (date.year, date.month, date.day),
+        _ => {
+            errors.push(format!("{context} `{datetime}`, which is not a date"));
+            (0, 0, 0)
+        }
+    }
+}
+
+fn optional_date(date: Option<Ymd>) -> String {
+    match date {
+        Some((year, month, day)) => {
+            format!("Some(NaiveDate::from_ymd_opt({year}, {month}, {day}).unwrap())")
+        }
+        None => "None".to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubSnapshot {
+    repositories: BTreeMap<String, GithubRepository>,
+}
+
+#[derive(Deserialize)]
+struct GithubRepository {
+    stars: u64,
+    /// yyyy-mm-dd
+    last_commit: Option<String>,
+    language: Option<String>,
+    license: Option<String>,
+}
+
+/// Generate `$OUT_DIR/github_metadata.rs`, the repository metadata in the
+/// github.json snapshot keyed by `owner/repo`. This only reads the snapshot,
+/// so it works offline, and is skipped (leaving no metadata) unless the
+/// `github-metadata` feature is enabled, in which case the snapshot must exist.
+/// It's fetched by the `fetch-github-metadata` subcommand, which is behind the
+/// separate `github-metadata-fetch` feature so that it builds without one.
+fn write_github_metadata(manifest_path: &Path, out_path: &Path) {
+    let mut generated = String::from("static GITHUB_METADATA: &[(&str, RepoMetadata)] = &[\n");
+
+    let snapshot_path = manifest_path.join("github.json");
+    if env::var_os("CARGO_FEATURE_GITHUB_METADATA").is_some() {
+        if !snapshot_path.exists() {
+            panic!(
+                "github.json not found, but the github-metadata feature shows it; fetch it with `cargo run --features github-metadata-fetch -- fetch-github-metadata`"
+            );
+        }
+        let snapshot = fs::read_to_string(&snapshot_path).expect("failed to read github.json");
+        let snapshot: GithubSnapshot =
+            serde_json::from_str(&snapshot).unwrap_or_else(|e| panic!("invalid github.json: {e}"));
+
+        for (repo, metadata) in snapshot.repositories {
+            let last_commit = metadata.last_commit.map(|date| {
+                let mut ymd = date.split('-').map(|n| {
+                    n.parse::<u32>()
+                        .unwrap_or_else(|_| panic!("invalid last_commit `{date}` in github.json"))
+                });
+                (
+                    ymd.next().unwrap_or_default(),
+                    ymd.next().unwrap_or_default(),
+                    ymd.next().unwrap_or_default(),
+                )
+            });
+            let optional = |s: &Option<String>| match s {
+                Some(s) => format!("Some({s:?})"),
+                None => "None".to_string(),
+            };
+
+            generated.push_str(&format!(
+                "    ({repo:?}, RepoMetadata {{ stars: {}, last_commit: {}, language: {}, license: {} }}),\n",
+                metadata.stars,
+                match last_commit {
+                    Some((year, month, day)) =>
+                        format!("NaiveDate::from_ymd_opt({year}, {month}, {day})"),
+                    None => "None".to_string(),
+                },
+                optional(&metadata.language),
+                optional(&metadata.license),
+            ));
+        }
+    }
+
+    generated.push_str("];\n");
+
+    fs::write(out_path.join("github_metadata.rs"), generated)
+        .expect("failed to write github_metadata.rs");
+}
+
+/// Generate `$OUT_DIR/build_info.rs`, which identifies this build: the git
+/// commit it was built from, if any, and when it was built. `SOURCE_DATE_EPOCH`
+/// overrides the build time, for reproducible builds.
+fn write_build_info(manifest_path: &Path, out_path: &Path) {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(manifest_path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let git_sha = git(&["rev-parse", "HEAD"]);
+    let dirty = git(&["status", "--porcelain"]).is_some_and(|status| !status.is_empty());
+    let built_at = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system clock is before 1970")
+                .as_secs() as i64
+        });
+
+    let generated = format!(
+        "static BUILD_INFO: BuildInfo = BuildInfo {{ version: env!(\"CARGO_PKG_VERSION\"), git_sha: {git_sha:?}, dirty: {dirty}, built_at: {built_at} }};\n"
+    );
+
+    fs::write(out_path.join("build_info.rs"), generated).expect("failed to write build_info.rs");
+}
+
+/// Generate `$OUT_DIR/script_hashes.rs`, the SHA-256 hash of each script in
+/// /static, keyed by file name, for the Content-Security-Policy and the
+/// scripts' `integrity` attributes. (The site has no inline scripts; any added
+/// would need hashing here too.)
+fn write_script_hashes(out_path: &Path) {
+    let mut scripts = fs::read_dir(out_path.join("static"))
+        .expect("failed to read $OUT_DIR/static")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "js"))
+        .collect::<Vec<_>>();
+    scripts.sort();
+
+    let mut generated = String::from("static SCRIPT_HASHES: &[(&str, &str)] = &[\n");
+    for script in scripts {
+        let name = script.file_name().unwrap().to_string_lossy();
+        let contents = fs::read(&script).expect("failed to read script");
+        let hash = base64::engine::general_purpose::STANDARD.encode(Sha256::digest(contents));
+        generated.push_str(&format!("    ({name:?}, \"sha256-{hash}\"),\n"));
+    }
+    generated.push_str("];\n");
+
+    fs::write(out_path.join("script_hashes.rs"), generated)
+        .expect("failed to write script_hashes.rs");
 }
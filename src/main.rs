@@ -9,198 +9,201 @@
 //! 1. `Markup` generating functions
 //! 1. endpoint handlers
 //! 1. main, including the router
+//! 1. subcommands and servers for other protocols: gemini, gopher, and finger
+//! 1. PDF exports
+//! 1. comments, stored in SQLite
+//! 1. admin dashboard authentication
+//! 1. page view analytics
+//! 1. Prometheus metrics
+//! 1. health checks
+//! 1. access logging
+//! 1. OpenTelemetry trace export
+//! 1. security headers
+//! 1. rate limiting
 //! 1. utility functions
 
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 
 use axum::Json;
-use axum::extract::Path;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Form, Path, Query};
 use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Redirect, Response};
-use axum::{Router, routing::get};
+use axum::routing::{get, post};
+use axum::{Router, middleware};
 use chrono::NaiveDate;
 use lazy_static::lazy_static;
 use maud::{DOCTYPE, PreEscaped};
 use maud::{Markup, html};
-use pulldown_cmark::{Options, Parser, html};
+use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd, html};
 use rust_embed::Embed;
 use strum::{EnumIter, EnumString, IntoEnumIterator};
 use tokio::signal;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 // static resources
 
-const HOMEPAGE_BUTTONS: [Link; 4] = [
-    Link {
-        href: "/projects",
-        title: "Projects",
-        target: None,
-    },
-    Link {
-        href: "/posts",
-        title: "Posts",
-        target: None,
-    },
-    Link {
-        href: "https://github.com/theryangeary",
-        title: "GitHub",
-        target: Some("_blank"),
-    },
-    Link {
-        href: "https://www.linkedin.com/in/theryangeary/",
-        title: "LinkedIn",
-        target: Some("_blank"),
-    },
-];
+const SITE_URL: &str = "https://www.ryangeary.dev";
+const REPOSITORY_URL: &str = "https://github.com/theryangeary/www";
+
+/// homepage links which the gemini and gopher mirrors also serve; the others
+/// link to the web
+const MIRRORED_PATHS: [&str; 2] = ["/projects", "/posts"];
+
+/// average adult reading speed, used to estimate post reading time
+const WORDS_PER_MINUTE: usize = 200;
+
+/// replies nested deeper than this are no longer indented further
+const MAX_COMMENT_INDENT: usize = 4;
+
+/// how many days the admin dashboard's page view stats can cover
+const ADMIN_STATS_PERIODS: [u32; 4] = [7, 30, 90, 365];
 
 lazy_static! {
     static ref POSTS: Vec<Post> = vec![
         Post {
             title: "Working with Multiple Files in Vim",
-            date: NaiveDate::from_ymd_opt(2019, 01, 02).unwrap(),
+            date: NaiveDate::from_ymd_opt(2019, 1, 2).unwrap(),
             tags: vec!["vim"],
             excerpt: "I was recently asked by another member of udellug about my \"top three tips for working with multiple files/large projects in vim\". Three quickly turned into six.",
             content: include_str!("../posts/2019-vim-tips.md"),
             id: "2019-vim-tips",
+            updated: None,
         },
         Post {
             title: "Making FZF Completion Automatic in ZSH",
-            date: NaiveDate::from_ymd_opt(2025, 07, 25).unwrap(),
+            date: NaiveDate::from_ymd_opt(2025, 7, 25).unwrap(),
             tags: vec!["zsh", "fzf"],
             excerpt: "I'm forcing myself to use FZF by triggering it on spacebar with commands that can benefit from it.",
             content: include_str!("../posts/2025-zsh-zle-fzf.md"),
             id: "2025-zsh-zle-fzf",
+            updated: None,
         },
         Post {
             title: "Why Oh Why Am I Starting a Homelab",
-            date: NaiveDate::from_ymd_opt(2025, 08, 10).unwrap(),
+            date: NaiveDate::from_ymd_opt(2025, 8, 10).unwrap(),
             tags: vec!["homelab", "fly.io"],
             excerpt: "After evaluating a handful of options for free-tier and cheap cloud hosting, I'm foraying into the wacky world of self-hosting.",
             content: include_str!("../posts/2025-homelab-1.md"),
             id: "2025-homelab-1",
+            updated: None,
         },
         Post {
             title: "Why I'm Making My Own Grocery List",
-            date: NaiveDate::from_ymd_opt(2025, 09, 10).unwrap(),
+            date: NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(),
             tags: vec!["rust", "react", "gl", "homelab"],
             excerpt: "Typing is lame. Pressing buttons is cool 😎. I buy the same things from the grocery store ALL the time. You probably do too.",
             content: include_str!("../posts/2025-why-gl.md"),
             id: "2025-why-gl",
+            updated: None,
         },
         Post {
             title: "Grocery List Demo is Now Live",
             date: NaiveDate::from_ymd_opt(2025, 10, 14).unwrap(),
             tags: vec!["gl", "homelab"],
-            excerpt: "Take it for a spin!", 
+            excerpt: "Take it for a spin!",
             content: include_str!("../posts/2025-gl-demo.md"),
             id: "2025-gl-demo",
+            updated: None,
         },
         Post {
             title: "I Caused a Security Vulnerability Today",
-            date: NaiveDate::from_ymd_opt(2025, 11, 05).unwrap(),
+            date: NaiveDate::from_ymd_opt(2025, 11, 5).unwrap(),
             tags: vec![tag::DOCKER, tag::HOMELAB, tag::MTA_DISPLAY, tag::CADDY],
             excerpt: "It doesn't help that I have absolutely no automated observability yet.",
             content: include_str!("../posts/2025-homelab-sec-vuln.md"),
             id: "2025-homelab-sec-vuln",
+            updated: None,
         }
     ];
 
-    static ref PROJECTS: Vec<Project> = vec![
-        Project {
-            id: "choose".to_string(),
-            title: "choose".to_string(),
-            description: "A human-friendly and fast alternative to cut (and sometimes awk).".to_string(),
-            tech_stack: vec![tag::RUST],
-            github_url: Some("https://github.com/theryangeary/choose".to_string()),
-            try_it_url: Some("https://github.com/theryangeary/choose?tab=readme-ov-file#installing-from-source".to_string()),
-            category: ProjectCategory::Production,
-        },
-        Project {
-            id: "personal-website".to_string(),
-            title: "Personal Website".to_string(),
-            description: "This site! Built with Rust (maud + axum), htmx, and Tailwind CSS. Compiles to a single binary with all static resources included.".to_string(),
-            tech_stack: vec![tag::RUST, tag::HTMX, tag::TAILWIND, tag::MAUD, tag::AXUM],
-            github_url: Some("https://github.com/theryangeary/www".to_string()),
-            try_it_url: Some("https://www.ryangeary.dev".to_string()),
-            category: ProjectCategory::Production,
-        },
-        Project {
-            id: "homelab".to_string(),
-            title: "Homelab".to_string(),
-            description: "My personal infrastructure, hosted on a Raspberry Pi running docker swarm in my router closet.".to_string(),
-            tech_stack: vec![tag::DOCKER, tag::CLOUDFLARE_TUNNELS, tag::CADDY],
-            github_url: Some("https://github.com/theryangeary/homelab".to_string()),
-            try_it_url: Some("https://www.ryangeary.dev".to_string()),
-            category: ProjectCategory::Production,
-        },
-        Project {
-            id: "fib-o1".to_string(),
-            title: "fib-o1: Constant Time Fibonacci Sequence Values".to_string(),
-            description: "Abusing the Rust build system to provide O(1) fib(n) at runtime.".to_string(),
-            tech_stack: vec![tag::RUST, tag::BIGINT],
-            github_url: Some("https://github.com/theryangeary/fib-o1".to_string()),
-            try_it_url: Some("https://crates.io/crates/fib-o1".to_string()),
-            category: ProjectCategory::Toy,
-        },
-        Project {
-            id: "pathfinder".to_string(),
-            title: "Pathfinder.prof".to_string(),
-            description: "A daily word puzzle combining points-based tiles with grid-based word finding.".to_string(),
-            tech_stack: vec![tag::FLY_IO, tag::CLOUDFLARE_PAGES, tag::POSTGRES, tag::RUST, tag::REACT, tag::TYPESCRIPT],
-            github_url: Some("https://github.com/theryangeary/pathfinder".to_string()),
-            try_it_url: Some("https://pathfinder.prof".to_string()),
-            category: ProjectCategory::Production,
-        },
-        Project {
-            id: "gl".to_string(),
-            title: "gl".to_string(),
-            description: "A personal-software grocery list featuring multi-player, autocomplete, and smart categorization".to_string(),
-            tech_stack: vec![tag::HOMELAB, tag::SQLITE, tag::RUST, tag::REACT, tag::TYPESCRIPT],
-            github_url: Some("https://github.com/theryangeary/gl".to_string()),
-            try_it_url: Some("https://gldemo.ryangeary.dev".to_string()),
-            category: ProjectCategory::Production,
-        },
-        Project {
-            id: "ginh".to_string(),
-            title: "Ginh Is Not a Histogram".to_string(),
-            description: "A shell-based visual representation of a user's shell history.".to_string(),
-            tech_stack: vec![tag::BASH, "That's it it's pure bash script"],
-            github_url: Some("https://github.com/crclark96/ginh".to_string()),
-            try_it_url: Some("https://github.com/crclark96/ginh?tab=readme-ov-file#installation".to_string()),
-            category: ProjectCategory::Toy,
-        },
-        Project {
-            id: "photo".to_string(),
-            title: "Photography Gallery Website".to_string(),
-            description: "Photo gallery website made with pure vanilla javascript components.".to_string(),
-            tech_stack: vec![tag::BASH, tag::JAVASCRIPT, tag::EXIFTOOL],
-            github_url: Some("https://github.com/theryangeary/photo".to_string()),
-            try_it_url: Some("https://theryangeary.github.io/photo".to_string()),
-            category: ProjectCategory::Production,
-        },
-        Project {
-            id: "mta-display".to_string(),
-            title: "MTA Subway Train Display".to_string(),
-            description: "A simulation of MTA displays with user-specified messages, complete with guestbook.".to_string(),
-            tech_stack: vec![tag::RUST, tag::HTMX, tag::TAILWIND, tag::MAUD, tag::AXUM, tag::SQLITE],
-            github_url: Some("https://github.com/theryangeary/mta-display".to_string()),
-            try_it_url: Some("https://mtadisplay.ryangeary.dev".to_string()),
-            category: ProjectCategory::Production,
-        }
-    ];
+    /// generated by build.rs from series.toml
+    static ref SERIES: Vec<Series> = include!(concat!(env!("OUT_DIR"), "/series.rs"));
+
+    /// generated by build.rs from projects.toml, in tab order
+    static ref PROJECT_CATEGORIES: Vec<ProjectCategory> =
+        include!(concat!(env!("OUT_DIR"), "/project_categories.rs"));
+
+    /// generated by build.rs from projects.toml
+    static ref PROJECTS: Vec<Project> = include!(concat!(env!("OUT_DIR"), "/projects.rs"));
+
+    /// generated by build.rs from homepage.toml
+    static ref HOMEPAGE: Homepage = include!(concat!(env!("OUT_DIR"), "/homepage.rs"));
+
+    /// generated by build.rs from resume.toml
+    static ref RESUME: Resume = include!(concat!(env!("OUT_DIR"), "/resume.rs"));
+
+    /// posts and projects related to each post, in the same order as `POSTS`
+    static ref RELATED: Vec<Related> = POSTS.iter().map(Related::for_post).collect();
+
+    /// the project timeline, newest first
+    static ref TIMELINE: Vec<TimelineEvent> = timeline_events();
 }
 
 #[derive(Embed)]
 #[folder = "$OUT_DIR/static"]
 struct Assets;
 
+include!(concat!(env!("OUT_DIR"), "/script_hashes.rs"));
+
+/// the `integrity` attribute for a script in /static, e.g. `htmx.min.js`
+fn script_integrity(file: &str) -> Option<&'static str> {
+    SCRIPT_HASHES
+        .iter()
+        .find(|(name, _)| *name == file)
+        .map(|(_, hash)| *hash)
+}
+
 // domain models
 
+/// the name, tagline, headshot, and buttons on the homepage, generated by
+/// build.rs from homepage.toml
+struct Homepage {
+    name: &'static str,
+    /// lines shown under the name; empty to show current roles from the résumé
+    tagline: Vec<&'static str>,
+    headshot: Option<Headshot>,
+    buttons: Vec<Link>,
+}
+
+impl Homepage {
+    fn tagline(&self) -> Vec<String> {
+        if self.tagline.is_empty() {
+            RESUME.roles().collect()
+        } else {
+            self.tagline.iter().map(|line| line.to_string()).collect()
+        }
+    }
+}
+
+struct Headshot {
+    /// path of the image, e.g. /static/headshot.jpg
+    src: &'static str,
+    alt: &'static str,
+}
+
 struct Link {
     href: &'static str,
     title: &'static str,
     target: Option<&'static str>,
+    icon: Option<Icon>,
+    /// whether this is another profile of mine, marked `rel="me"` for identity verification
+    rel_me: bool,
+}
+
+/// an icon shown on a homepage button
+#[derive(Clone, Copy)]
+enum Icon {
+    Folder,
+    Pencil,
+    Document,
+    GitHub,
+    LinkedIn,
 }
 
 struct Post {
@@ -216,6 +219,8 @@ struct Post {
     excerpt: &'static str,
     /// markdown content of document
     content: &'static str,
+    /// date of last meaningful update, overriding the one derived from git history
+    updated: Option<chrono::NaiveDate>,
 }
 
 impl Post {
@@ -226,66 +231,774 @@ impl Post {
     fn formatted_date(&self) -> String {
         self.date.to_string()
     }
+
+    /// destinations of the links in the post's content
+    fn link_targets(&self) -> impl Iterator<Item = String> {
+        Parser::new(self.content).filter_map(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. }) => Some(dest_url.to_string()),
+            _ => None,
+        })
+    }
+
+    /// title, date, and body of the post as plain text
+    fn plaintext(&self) -> String {
+        format!(
+            "{}\n{}\n\n{}\n\n{}",
+            self.title,
+            "=".repeat(self.title.chars().count()),
+            self.formatted_date(),
+            markdown_to_plaintext(self.content)
+        )
+    }
+
+    /// git revisions of this post's markdown file, newest first
+    fn revisions(&self) -> &'static [Revision] {
+        POST_HISTORY
+            .iter()
+            .find(|(id, _)| *id == self.id)
+            .map(|(_, revisions)| *revisions)
+            .unwrap_or_default()
+    }
+
+    fn stats(&self) -> PostStats {
+        POST_STATS
+            .iter()
+            .find(|(id, _)| *id == self.id)
+            .map(|(_, stats)| *stats)
+            .unwrap_or_default()
+    }
+
+    /// estimated minutes to read, rounded up
+    fn reading_time_minutes(&self) -> usize {
+        self.stats().word_count.div_ceil(WORDS_PER_MINUTE).max(1)
+    }
+
+    fn formatted_reading_time(&self) -> String {
+        format!(
+            "{} min read · {} words",
+            self.reading_time_minutes(),
+            self.stats().word_count
+        )
+    }
+
+    fn related(&self) -> &'static Related {
+        let index = find_post(self.id)
+            .map(|(index, _)| index)
+            .unwrap_or_default();
+        &RELATED[index]
+    }
+
+    /// the series this post belongs to, if any, and its 1-indexed part number within it
+    fn series(&self) -> Option<(&'static Series, usize)> {
+        SERIES.iter().find_map(|series| {
+            series
+                .post_ids
+                .iter()
+                .position(|id| *id == self.id)
+                .map(|position| (series, position + 1))
+        })
+    }
+
+    /// the explicit `updated` date if set, otherwise the latest revision made after publication
+    fn updated(&self) -> Option<NaiveDate> {
+        self.updated.or_else(|| {
+            self.revisions()
+                .iter()
+                .map(|r| r.date)
+                .filter(|d| *d > self.date)
+                .max()
+        })
+    }
+}
+
+/// representations a post can be served in
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PostFormat {
+    Html,
+    /// the original markdown source
+    Markdown,
+    /// rendered from the markdown, for reading or pasting where html isn't welcome
+    PlainText,
+    /// rendered from the markdown, for printing or attaching to documents
+    Pdf,
+}
+
+impl PostFormat {
+    fn from_extension(extension: &str) -> Option<PostFormat> {
+        match extension {
+            "html" => Some(PostFormat::Html),
+            "md" => Some(PostFormat::Markdown),
+            "txt" => Some(PostFormat::PlainText),
+            "pdf" => Some(PostFormat::Pdf),
+            _ => None,
+        }
+    }
+
+    fn media_type(&self) -> &str {
+        match self {
+            PostFormat::Html => "text/html",
+            PostFormat::Markdown => "text/markdown",
+            PostFormat::PlainText => "text/plain",
+            PostFormat::Pdf => "application/pdf",
+        }
+    }
+
+    /// pick the format an `Accept` header rates highest, preferring html, then
+    /// the other formats in declaration order, among equally rated ones. Html is
+    /// also the fallback when the header is missing or accepts none of them.
+    fn from_accept(headers: &HeaderMap) -> PostFormat {
+        let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+            return PostFormat::Html;
+        };
+
+        // (type, subtype, q) for each media range, e.g. ("text", "*", 0.5)
+        let ranges = accept
+            .split(',')
+            .filter_map(|range| {
+                let mut params = range.split(';');
+                let (kind, subtype) = params.next()?.trim().split_once('/')?;
+                let q = params
+                    .filter_map(|param| param.split_once('='))
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                    .map_or(Some(1.0), |(_, q)| q.trim().parse::<f32>().ok())?;
+                Some((kind.trim(), subtype.trim(), q))
+            })
+            .collect::<Vec<_>>();
+
+        // a format's q comes from the most specific range matching it:
+        // `text/plain` over `text/*` over `*/*`
+        let quality = |format: PostFormat| {
+            let (kind, subtype) = format.media_type().split_once('/').unwrap();
+            ranges
+                .iter()
+                .filter_map(|&(range_kind, range_subtype, q)| {
+                    let specificity = match (range_kind, range_subtype) {
+                        ("*", "*") => 0,
+                        (k, "*") if k.eq_ignore_ascii_case(kind) => 1,
+                        (k, s)
+                            if k.eq_ignore_ascii_case(kind) && s.eq_ignore_ascii_case(subtype) =>
+                        {
+                            2
+                        }
+                        _ => return None,
+                    };
+                    Some((specificity, q))
+                })
+                .max_by_key(|&(specificity, _)| specificity)
+                .map_or(0.0, |(_, q)| q)
+        };
+
+        [
+            PostFormat::Html,
+            PostFormat::Markdown,
+            PostFormat::PlainText,
+            PostFormat::Pdf,
+        ]
+        .into_iter()
+        .map(|format| (format, quality(format)))
+        // q=0 means "not acceptable"
+        .filter(|&(_, q)| q > 0.0)
+        // the highest rated, and the first of equally rated (`max_by` would give the last)
+        .min_by(|(_, a), (_, b)| b.total_cmp(a))
+        .map_or(PostFormat::Html, |(format, _)| format)
+    }
+
+    fn extension(&self) -> &str {
+        match self {
+            PostFormat::Html => "",
+            PostFormat::Markdown => ".md",
+            PostFormat::PlainText => ".txt",
+            PostFormat::Pdf => ".pdf",
+        }
+    }
+}
+
+/// a commit which touched a post's markdown file, generated by build.rs from git history
+struct Revision {
+    sha: &'static str,
+    date: chrono::NaiveDate,
+    /// commit subject line
+    summary: &'static str,
+    /// GitHub's anchor for the post's file in the commit's diff: `diff-` and the
+    /// SHA-256 of its path (as of that commit) in hex
+    file_anchor: &'static str,
+}
+
+impl Revision {
+    fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+
+    fn diff_url(&self) -> String {
+        format!("{REPOSITORY_URL}/commit/{}#{}", self.sha, self.file_anchor)
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/post_history.rs"));
+
+/// statistics about a post's markdown, generated by build.rs
+#[derive(Clone, Copy, Default)]
+struct PostStats {
+    /// words of prose, excluding code blocks and footnotes
+    word_count: usize,
+}
+
+include!(concat!(env!("OUT_DIR"), "/post_stats.rs"));
+
+/// recommendations shown at the bottom of a post
+struct Related {
+    /// indices into `POSTS`, most related first
+    posts: Vec<usize>,
+    /// most related first
+    projects: Vec<&'static Project>,
+}
+
+impl Related {
+    const MAX_POSTS: usize = 3;
+    const MAX_PROJECTS: usize = 3;
+
+    /// weight of full vocabulary overlap relative to a single shared tag
+    const TEXT_SIMILARITY_WEIGHT: f64 = 4.0;
+
+    fn for_post(post: &Post) -> Related {
+        let tags = post.tags.iter().map(|t| slug(t)).collect::<HashSet<_>>();
+        let words = vocabulary(post.content);
+
+        let mut posts = POSTS
+            .iter()
+            .enumerate()
+            .filter(|(_, other)| other.id != post.id)
+            .filter_map(|(index, other)| {
+                let shared_tags = other
+                    .tags
+                    .iter()
+                    .filter(|t| tags.contains(&slug(t)))
+                    .count();
+                // nearly every pair of posts shares some vocabulary, so only a shared tag makes a post related
+                if shared_tags == 0 {
+                    return None;
+                }
+                let other_vocabulary = vocabulary(other.content);
+                let similarity = words.intersection(&other_vocabulary).count() as f64
+                    / words.union(&other_vocabulary).count().max(1) as f64;
+                Some((
+                    index,
+                    shared_tags as f64 + similarity * Self::TEXT_SIMILARITY_WEIGHT,
+                ))
+            })
+            .collect::<Vec<_>>();
+        posts.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        // a project named by a tag (e.g. the `gl` posts) is a stronger match than shared technologies
+        let mut projects = PROJECTS
+            .iter()
+            .map(|project| {
+                let named = tags.contains(&slug(project.id)) as usize;
+                let shared_tech = project
+                    .tech_stack
+                    .iter()
+                    .filter(|t| tags.contains(&slug(t)))
+                    .count();
+                (project, named * 3 + shared_tech)
+            })
+            .filter(|(_, score)| *score > 0)
+            .collect::<Vec<_>>();
+        projects.sort_by_key(|(_, score)| Reverse(*score));
+
+        Related {
+            posts: posts
+                .into_iter()
+                .take(Self::MAX_POSTS)
+                .map(|(index, _)| index)
+                .collect(),
+            projects: projects
+                .into_iter()
+                .take(Self::MAX_PROJECTS)
+                .map(|(project, _)| project)
+                .collect(),
+        }
+    }
+}
+
+/// a group of posts which are meant to be read in order
+struct Series {
+    /// a unique id for this series; is also used in URLs
+    id: &'static str,
+    /// title of series; displayed in series page and posts of the series
+    title: &'static str,
+    /// summary of series; displayed in series page
+    description: &'static str,
+    /// ids of the posts in this series, in reading order
+    post_ids: Vec<&'static str>,
+}
+
+impl Series {
+    /// posts in this series in reading order, with their index in `POSTS`
+    fn posts(&self) -> impl Iterator<Item = (usize, &'static Post)> {
+        self.post_ids.iter().filter_map(|id| find_post(id))
+    }
+
+    fn url(&self) -> String {
+        format!("/series/{}", self.id)
+    }
 }
 
+/// the tag taxonomy, generated by build.rs from tags.toml
 mod tag {
     pub type Tag = &'static str;
 
-    pub const RUST: Tag = "Rust";
-    pub const AXUM: Tag = "Axum";
-    pub const MAUD: Tag = "Maud";
-    pub const HTMX: Tag = "htmx";
-    pub const TAILWIND: Tag = "Tailwind CSS";
-    pub const DOCKER: Tag = "Docker";
-    pub const CLOUDFLARE_TUNNELS: Tag = "Cloudflare Tunnels";
-    pub const CADDY: Tag = "Caddy";
-    pub const BIGINT: Tag = "BigInt";
-    pub const FLY_IO: Tag = "Fly.io";
-    pub const CLOUDFLARE_PAGES: Tag = "Cloudflare Pages";
-    pub const POSTGRES: Tag = "PostgreSQL";
-    pub const REACT: Tag = "React";
-    pub const TYPESCRIPT: Tag = "TypeScript";
-    pub const HOMELAB: Tag = "Homelab";
-    pub const SQLITE: Tag = "SQLite";
-    pub const BASH: Tag = "Bash";
-    pub const JAVASCRIPT: Tag = "JavaScript";
-    pub const EXIFTOOL: Tag = "ExifTool";
-    pub const MTA_DISPLAY: Tag = "MTA Display";
+    include!(concat!(env!("OUT_DIR"), "/tags.rs"));
 }
 
 struct Project {
-    #[allow(dead_code)]
-    id: String,
-    title: String,
-    description: String,
+    id: &'static str,
+    title: &'static str,
+    description: &'static str,
     tech_stack: Vec<tag::Tag>,
-    github_url: Option<String>,
-    try_it_url: Option<String>,
-    category: ProjectCategory,
+    github_url: Option<&'static str>,
+    try_it_url: Option<&'static str>,
+    /// long-form markdown write-up; displayed in project page
+    writeup: Option<&'static str>,
+    /// images from `Assets`; displayed in project page
+    screenshots: Vec<Screenshot>,
+    category: &'static ProjectCategory,
+    /// unset when unknown
+    status: Option<ProjectStatus>,
+    /// when work began; undated projects are left off the timeline
+    started: Option<chrono::NaiveDate>,
+    /// when work stopped, if it has
+    ended: Option<chrono::NaiveDate>,
+}
+
+impl Project {
+    fn url(&self) -> String {
+        format!("/projects/{}/{}", self.category, self.id)
+    }
+
+    /// `owner/repo`, if the project is on GitHub
+    fn github_repo(&self) -> Option<&'static str> {
+        self.github_url?
+            .strip_prefix("https://github.com/")
+            .map(|repo| repo.trim_end_matches('/'))
+    }
+
+    fn repo_metadata(&self) -> Option<&'static RepoMetadata> {
+        let repo = self.github_repo()?;
+        GITHUB_METADATA
+            .iter()
+            .find(|(r, _)| *r == repo)
+            .map(|(_, metadata)| metadata)
+    }
+
+    /// e.g. "2025-08 – present", if the project is dated
+    fn formatted_lifespan(&self) -> Option<String> {
+        let unended = match self.status {
+            Some(ProjectStatus::Archived) => "?",
+            _ => "present",
+        };
+        formatted_date_range(self.started, self.ended, unended)
+    }
+
+    /// posts tagged with this project, or linking to its code or demo
+    fn posts(&self) -> impl Iterator<Item = (usize, &'static Post)> {
+        POSTS.iter().enumerate().filter(|(_, p)| {
+            p.tags.iter().any(|t| slug(t) == slug(self.id))
+                || p.link_targets().any(|target| self.is_linked_by(&target))
+        })
+    }
+
+    /// whether a link to `target` is a link to this project's code or demo;
+    /// a demo hosted on this site doesn't count, as most posts link to the site
+    fn is_linked_by(&self, target: &str) -> bool {
+        let target = target.trim_end_matches('/');
+        let in_repository = self.github_url.is_some_and(|url| {
+            target
+                .strip_prefix(url.trim_end_matches('/'))
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '#', '?']))
+        });
+        let is_demo = self
+            .try_it_url
+            .filter(|url| !url.starts_with(SITE_URL))
+            .is_some_and(|url| target == url.trim_end_matches('/'));
+        in_repository || is_demo
+    }
+}
+
+/// activity signals for a project's GitHub repository, generated by build.rs
+/// from the github.json snapshot when the `github-metadata` feature is enabled
+struct RepoMetadata {
+    stars: u64,
+    last_commit: Option<chrono::NaiveDate>,
+    /// primary language, as detected by GitHub
+    language: Option<&'static str>,
+    /// SPDX id, e.g. MIT
+    license: Option<&'static str>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/github_metadata.rs"));
+
+struct Screenshot {
+    /// path of the image, e.g. /static/gl_items.png
+    src: &'static str,
+    alt: &'static str,
 }
 
-#[derive(EnumIter, EnumString, PartialEq, Eq, Hash, strum::Display, Copy, Clone)]
+/// a tab of the project page, generated by build.rs from projects.toml
+#[derive(PartialEq, Eq)]
+struct ProjectCategory {
+    /// a unique id for this category; is also used in URLs
+    slug: &'static str,
+    /// displayed in the category's tab
+    title: &'static str,
+    /// displayed above the category's projects
+    description: &'static str,
+}
+
+impl ProjectCategory {
+    fn find(slug: &str) -> Option<&'static ProjectCategory> {
+        PROJECT_CATEGORIES.iter().find(|c| c.slug == slug)
+    }
+
+    /// the first category, which /projects shows
+    fn first() -> &'static ProjectCategory {
+        &PROJECT_CATEGORIES[0]
+    }
+
+    fn current_projects(&self) -> impl Iterator<Item = &'static Project> + '_ {
+        PROJECTS.iter().filter(move |p| p.category == self)
+    }
+}
+
+impl std::fmt::Display for ProjectCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.slug)
+    }
+}
+
+/// where a project is in its lifecycle, from most to least alive
+// variants are only constructed by build.rs, for projects whose status is set
+#[allow(dead_code)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+enum ProjectStatus {
+    /// under active development
+    Active,
+    /// done, but kept working
+    Maintained,
+    /// built to try something out
+    Experiment,
+    /// no longer worked on
+    Archived,
+}
+
+impl ProjectStatus {
+    fn title(&self) -> &str {
+        match self {
+            ProjectStatus::Active => "Active",
+            ProjectStatus::Maintained => "Maintained",
+            ProjectStatus::Experiment => "Experiment",
+            ProjectStatus::Archived => "Archived",
+        }
+    }
+
+    fn classes(&self) -> &str {
+        match self {
+            ProjectStatus::Active => {
+                "bg-green-100 dark:bg-green-900/30 text-green-800 dark:text-green-300"
+            }
+            ProjectStatus::Maintained => {
+                "bg-sky-100 dark:bg-sky-900/30 text-sky-800 dark:text-sky-300"
+            }
+            ProjectStatus::Experiment => {
+                "bg-amber-100 dark:bg-amber-900/30 text-amber-800 dark:text-amber-300"
+            }
+            ProjectStatus::Archived => {
+                "bg-gray-200 dark:bg-gray-700 text-gray-700 dark:text-gray-300"
+            }
+        }
+    }
+}
+
+/// the ordering of the project grid
+#[derive(EnumIter, EnumString, PartialEq, Eq, strum::Display, Copy, Clone, Default)]
 #[strum(serialize_all = "snake_case")]
-#[derive(Default)]
-enum ProjectCategory {
+enum ProjectSort {
+    /// the order of projects.toml
     #[default]
-    Production,
-    Toy,
+    Featured,
+    Title,
+    /// most recently started first, undated projects last
+    Recency,
+    /// most alive first
+    Status,
 }
 
-impl ProjectCategory {
+impl ProjectSort {
     fn title(&self) -> &str {
         match self {
-            ProjectCategory::Production => "Production Projects",
-            ProjectCategory::Toy => "Toy Projects",
+            ProjectSort::Featured => "Featured",
+            ProjectSort::Title => "Title",
+            ProjectSort::Recency => "Recency",
+            ProjectSort::Status => "Status",
+        }
+    }
+}
+
+/// which projects to show in the project grid, and in what order; kept in the
+/// query string as e.g. `?tech=rust,sqlite&sort=title`
+#[derive(Clone, Default)]
+struct ProjectFilter {
+    /// only show projects using all of these
+    tech: Vec<tag::Tag>,
+    sort: ProjectSort,
+}
+
+impl ProjectFilter {
+    /// unknown technologies and sorts are ignored
+    fn from_query(query: &HashMap<String, String>) -> ProjectFilter {
+        let all_tech = all_tech();
+        ProjectFilter {
+            tech: query
+                .get("tech")
+                .map(|tech| {
+                    tech.split(',')
+                        .filter_map(|s| all_tech.iter().find(|t| slug(t) == s).copied())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            sort: query
+                .get("sort")
+                .and_then(|sort| ProjectSort::from_str(sort).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// the query string for this filter, including the leading `?` if non-empty
+    fn query_string(&self) -> String {
+        let mut params = vec![];
+        if !self.tech.is_empty() {
+            let tech = self.tech.iter().map(|t| slug(t)).collect::<Vec<_>>();
+            params.push(format!("tech={}", tech.join(",")));
+        }
+        if self.sort != ProjectSort::default() {
+            params.push(format!("sort={}", self.sort));
+        }
+        if params.is_empty() {
+            String::new()
+        } else {
+            format!("?{}", params.join("&"))
+        }
+    }
+
+    fn toggle_tech(&self, tech: tag::Tag) -> ProjectFilter {
+        let mut filter = self.clone();
+        match filter.tech.iter().position(|t| *t == tech) {
+            Some(i) => {
+                filter.tech.remove(i);
+            }
+            None => filter.tech.push(tech),
+        }
+        filter
+    }
+
+    fn with_sort(&self, sort: ProjectSort) -> ProjectFilter {
+        ProjectFilter {
+            sort,
+            ..self.clone()
+        }
+    }
+
+    fn apply<'a>(&self, projects: impl Iterator<Item = &'a Project>) -> Vec<&'a Project> {
+        let mut projects = projects
+            .filter(|p| self.tech.iter().all(|t| p.tech_stack.contains(t)))
+            .collect::<Vec<_>>();
+        match self.sort {
+            ProjectSort::Featured => {}
+            ProjectSort::Title => projects.sort_by_key(|p| p.title.to_lowercase()),
+            ProjectSort::Recency => projects.sort_by_key(|p| Reverse(p.started)),
+            // projects of unknown status last
+            ProjectSort::Status => projects.sort_by_key(|p| (p.status.is_none(), p.status)),
+        }
+        projects
+    }
+}
+
+/// every technology used by any project, most used first
+fn all_tech() -> Vec<tag::Tag> {
+    tech_used_by(PROJECTS.iter())
+}
+
+/// every technology used by any of `projects`, most used first
+fn tech_used_by<'a>(projects: impl Iterator<Item = &'a Project>) -> Vec<tag::Tag> {
+    let mut counts: HashMap<tag::Tag, usize> = HashMap::new();
+    for tech in projects.flat_map(|p| &p.tech_stack) {
+        *counts.entry(tech).or_default() += 1;
+    }
+    let mut tech = counts.into_iter().collect::<Vec<_>>();
+    tech.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    tech.into_iter().map(|(t, _)| t).collect()
+}
+
+/// my résumé, generated by build.rs from resume.toml
+struct Resume {
+    name: &'static str,
+    /// a one-line headline, e.g. "Software Developer"
+    label: &'static str,
+    summary: Option<&'static str>,
+    profiles: Vec<Profile>,
+    /// most recent first
+    work: Vec<Work>,
+    education: Vec<Education>,
+    skills: Vec<SkillGroup>,
+}
+
+impl Resume {
+    /// current roles, e.g. "Software Developer @Lyft", as shown on the homepage
+    fn roles(&self) -> impl Iterator<Item = String> + '_ {
+        self.work
+            .iter()
+            .filter(|work| work.ended.is_none())
+            .map(Work::role)
+    }
+
+    /// this résumé in JSON Resume format, see <https://jsonresume.org/schema>
+    fn to_json_resume(&self) -> serde_json::Value {
+        use serde_json::{Value, json};
+
+        let date = |date: Option<NaiveDate>| date.map(|d| d.format("%Y-%m-%d").to_string());
+        // the schema has no nulls, so unknown fields are left out instead
+        let object = |value: Value| match value {
+            Value::Object(mut fields) => {
+                fields.retain(|_, v| !v.is_null());
+                Value::Object(fields)
+            }
+            value => value,
+        };
+
+        json!({
+            "$schema": "https://raw.githubusercontent.com/jsonresume/resume-schema/v1.0.0/schema.json",
+            "basics": object(json!({
+                "name": self.name,
+                "label": self.label,
+                "url": SITE_URL,
+                "summary": self.summary,
+                "profiles": self.profiles.iter().map(|profile| json!({
+                    "network": profile.network,
+                    "username": profile.username,
+                    "url": profile.url,
+                })).collect::<Vec<_>>(),
+            })),
+            "work": self.work.iter().map(|work| object(json!({
+                "name": work.organization,
+                "position": work.position,
+                "url": work.url,
+                "summary": work.summary,
+                "highlights": work.highlights,
+                "startDate": date(work.started),
+                "endDate": date(work.ended),
+            }))).collect::<Vec<_>>(),
+            "education": self.education.iter().map(|education| object(json!({
+                "institution": education.institution,
+                "area": education.area,
+                "studyType": education.study_type,
+                "url": education.url,
+                "startDate": date(education.started),
+                "endDate": date(education.ended),
+            }))).collect::<Vec<_>>(),
+            "skills": self.skills.iter().map(|group| json!({
+                "name": group.name,
+                "keywords": group.skills,
+            })).collect::<Vec<_>>(),
+            "projects": PROJECTS.iter().map(|project| object(json!({
+                "name": project.title,
+                "description": project.description,
+                "url": absolute_url(&project.url()),
+                "keywords": project.tech_stack,
+                "startDate": date(project.started),
+                "endDate": date(project.ended),
+            }))).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// a profile on another site, e.g. GitHub
+struct Profile {
+    network: &'static str,
+    username: &'static str,
+    url: &'static str,
+}
+
+struct Work {
+    position: &'static str,
+    /// who the work is for; independent work has none
+    organization: Option<&'static str>,
+    url: Option<&'static str>,
+    summary: Option<&'static str>,
+    highlights: Vec<&'static str>,
+    started: Option<NaiveDate>,
+    /// current work has no end date
+    ended: Option<NaiveDate>,
+}
+
+impl Work {
+    /// e.g. "Software Developer @Lyft"
+    fn role(&self) -> String {
+        match self.organization {
+            Some(organization) => format!("{} @{}", self.position, organization),
+            None => self.position.to_string(),
+        }
+    }
+}
+
+struct Education {
+    institution: &'static str,
+    /// e.g. Computer Science
+    area: &'static str,
+    /// e.g. Bachelor of Science
+    study_type: &'static str,
+    url: Option<&'static str>,
+    started: Option<NaiveDate>,
+    ended: Option<NaiveDate>,
+}
+
+/// a named group of skills from the tag taxonomy, e.g. Languages
+struct SkillGroup {
+    name: &'static str,
+    skills: Vec<tag::Tag>,
+}
+
+/// identifies the running build, generated by build.rs
+struct BuildInfo {
+    version: &'static str,
+    /// the commit built from, if built from a git checkout
+    git_sha: Option<&'static str>,
+    /// whether that checkout had uncommitted changes
+    dirty: bool,
+    /// seconds since the unix epoch
+    built_at: i64,
+}
+
+impl BuildInfo {
+    fn built_at(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(self.built_at, 0).unwrap_or_default()
+    }
+
+    /// e.g. `2895332`, `2895332-dirty`, or `unknown`
+    fn commit(&self) -> String {
+        match self.git_sha {
+            Some(sha) if self.dirty => format!("{}-dirty", &sha[..sha.len().min(7)]),
+            Some(sha) => sha[..sha.len().min(7)].to_string(),
+            None => "unknown".to_string(),
         }
     }
 
-    fn current_projects(&self) -> impl Iterator<Item = &Project> {
-        PROJECTS.iter().filter(|p| p.category == *self)
+    fn commit_url(&self) -> Option<String> {
+        self.git_sha
+            .map(|sha| format!("{REPOSITORY_URL}/commit/{sha}"))
     }
 }
 
+include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
+
 // markup generation
 
 fn head(title: &str) -> Markup {
@@ -295,7 +1008,7 @@ fn head(title: &str) -> Markup {
             meta charset="UTF-8" {};
             meta name="viewport" content="width=device-width, initial-scale=1.0" {};
             link rel="stylesheet" href="/static/output.css";
-            script src="/static/htmx.min.js" {};
+            script src="/static/htmx.min.js" integrity=[script_integrity("htmx.min.js")] {};
             title { (title) }
         }
     }
@@ -303,17 +1016,49 @@ fn head(title: &str) -> Markup {
 
 fn navbar() -> Markup {
     html! {
-        nav class="m-4" {
+        nav class="m-4 print:hidden" {
             div class="flex justify-center divide-x-1 divide-purple-300 divide-solid " {
-                a class="text-xl text-purple-900/50 dark:text-violet-300 hover:underline decoration-3 pr-3 pl-3 md:text-3xl flex-1 " href="/" { "Ryan Geary" }
+                a class="text-xl text-purple-900/50 dark:text-violet-300 hover:underline decoration-3 pr-3 pl-3 md:text-3xl flex-1 " href="/" { (HOMEPAGE.name) }
                 a class="text-xl text-purple-900/50 dark:text-violet-300 hover:underline decoration-3 pr-3 pl-3 flex-none " href="/projects" { "Projects" }
                 a class="text-xl text-purple-900/50 dark:text-violet-300 hover:underline decoration-3 pr-3 pl-3 flex-none " href="/posts" { "Posts" }
+                a class="text-xl text-purple-900/50 dark:text-violet-300 hover:underline decoration-3 pr-3 pl-3 flex-none " href="/resume" { "Résumé" }
+            }
+        }
+    }
+}
+
+fn icon_markup(icon: Icon) -> Markup {
+    let class = "w-5 h-5 shrink-0";
+    match icon {
+        // brand marks are filled, the rest are outlines
+        Icon::GitHub => html! {
+            svg class=(class) viewBox="0 0 16 16" fill="currentColor" aria-hidden="true" {
+                path d="M8 0C3.58 0 0 3.58 0 8c0 3.54 2.29 6.53 5.47 7.59.4.07.55-.17.55-.38 0-.19-.01-.82-.01-1.49-2.01.37-2.53-.49-2.69-.94-.09-.23-.48-.94-.82-1.13-.28-.15-.68-.52-.01-.53.63-.01 1.08.58 1.23.82.72 1.21 1.87.87 2.33.66.07-.52.28-.87.51-1.07-1.78-.2-3.64-.89-3.64-3.95 0-.87.31-1.59.82-2.15-.08-.2-.36-1.02.08-2.12 0 0 .67-.21 2.2.82.64-.18 1.32-.27 2-.27.68 0 1.36.09 2 .27 1.53-1.04 2.2-.82 2.2-.82.44 1.1.16 1.92.08 2.12.51.56.82 1.27.82 2.15 0 3.07-1.87 3.75-3.65 3.95.29.25.54.73.54 1.48 0 1.07-.01 1.93-.01 2.2 0 .21.15.46.55.38A8.013 8.013 0 0016 8c0-4.42-3.58-8-8-8z" {}
+            }
+        },
+        Icon::LinkedIn => html! {
+            svg class=(class) viewBox="0 0 24 24" fill="currentColor" aria-hidden="true" {
+                path d="M20.447 20.452h-3.554v-5.569c0-1.328-.027-3.037-1.852-3.037-1.853 0-2.136 1.445-2.136 2.939v5.667H9.351V9h3.414v1.561h.046c.477-.9 1.637-1.85 3.37-1.85 3.601 0 4.267 2.37 4.267 5.455v6.286zM5.337 7.433c-1.144 0-2.063-.926-2.063-2.065 0-1.138.92-2.063 2.063-2.063 1.14 0 2.064.925 2.064 2.063 0 1.139-.925 2.065-2.064 2.065zm1.782 13.019H3.555V9h3.564v11.452zM22.225 0H1.771C.792 0 0 .774 0 1.729v20.542C0 23.227.792 24 1.771 24h20.451C23.2 24 24 23.227 24 22.271V1.729C24 .774 23.2 0 22.222 0h.003z" {}
+            }
+        },
+        icon => {
+            let d = match icon {
+                Icon::Folder => {
+                    "M3 7a2 2 0 0 1 2-2h4l2 2h8a2 2 0 0 1 2 2v8a2 2 0 0 1-2 2H5a2 2 0 0 1-2-2z"
+                }
+                Icon::Pencil => "M4 20h4L19 9l-4-4L4 16zM13 7l4 4",
+                _ => "M7 3h7l5 5v13H7zM14 3v5h5M10 13h6M10 17h6",
+            };
+            html! {
+                svg class=(class) viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" aria-hidden="true" {
+                    path d=(d) {}
+                }
             }
         }
     }
 }
 
-fn project_page_markup(category: ProjectCategory) -> Markup {
+fn project_page_markup(category: &ProjectCategory, filter: &ProjectFilter) -> Markup {
     html! {
         (head("Projects"))
         body {
@@ -321,7 +1066,7 @@ fn project_page_markup(category: ProjectCategory) -> Markup {
                 div class="container mx-auto px-4 py-4" {
                     (navbar())
                     div class="mt-8" {
-                        (project_tabs_markup(category))
+                        (project_tabs_markup(category, filter))
                     }
                 }
             }
@@ -329,7 +1074,7 @@ fn project_page_markup(category: ProjectCategory) -> Markup {
     }
 }
 
-fn project_tabs_markup(active: ProjectCategory) -> Markup {
+fn project_tabs_markup(active: &ProjectCategory, filter: &ProjectFilter) -> Markup {
     let all_tab_styles = "px-6 py-3 border-1 border-purple-300 font-medium transition-colors ";
     let inactive_tab_styles = "bg-gray-200 dark:bg-gray-700 text-gray-700 dark:text-gray-300 hover:bg-gray-300 dark:hover:bg-gray-600";
     let active_tab_styles = "bg-purple-900/75 text-amber-200 ";
@@ -337,20 +1082,84 @@ fn project_tabs_markup(active: ProjectCategory) -> Markup {
     html! {
         div id=(target_id) class="space-y-6 divide-solid divide-purple-300 divide-y-1" {
             div class="flex justify-center" {
-                @for tab in ProjectCategory::iter() {
+                @for tab in PROJECT_CATEGORIES.iter() {
                     @let classes = if tab == active {
                         all_tab_styles.to_owned() + active_tab_styles
                     } else {
                         all_tab_styles.to_owned() + inactive_tab_styles
                     };
 
-                    button class=(classes) hx-get=(format!("/projects/{}", tab.to_string())) hx-target=(id(target_id)){
-                        (tab.title())
+                    button class=(classes) hx-get=(format!("/projects/{}{}", tab, filter.query_string())) hx-target=(id(target_id)){
+                        (tab.title)
                     }
                 }
             }
 
-            (project_grid_markup(active.current_projects()))
+            p class="pt-6 text-center text-gray-700 dark:text-gray-300" {
+                (active.description)
+            }
+
+            (project_filter_markup(active, filter, target_id))
+
+            div class="pt-3 text-sm" {
+                a href="/projects/timeline" class="text-violet-600 dark:text-violet-400 hover:underline font-medium" {
+                    "View as a timeline →"
+                }
+            }
+
+            (project_grid_markup(filter.apply(active.current_projects()).into_iter()))
+        }
+    }
+}
+
+fn project_filter_markup(
+    active: &ProjectCategory,
+    filter: &ProjectFilter,
+    target_id: &str,
+) -> Markup {
+    let all_chip_styles = "px-2 py-1 rounded text-xs transition-colors ";
+    let inactive_chip_styles = "bg-violet-100 dark:bg-violet-900/30 text-violet-800 dark:text-violet-300 hover:bg-violet-200 dark:hover:bg-violet-900/60";
+    let active_chip_styles = "bg-purple-900/75 text-amber-200";
+    let chip_classes = |selected: bool| {
+        all_chip_styles.to_owned()
+            + if selected {
+                active_chip_styles
+            } else {
+                inactive_chip_styles
+            }
+    };
+    let href = |f: &ProjectFilter| format!("/projects/{}{}", active, f.query_string());
+    // technologies selected on another tab stay listed, so they can be deselected
+    let mut chips = tech_used_by(active.current_projects());
+    for tech in &filter.tech {
+        if !chips.contains(tech) {
+            chips.push(tech);
+        }
+    }
+
+    html! {
+        div class="pt-6 space-y-3" {
+            div class="flex flex-wrap items-center gap-2" {
+                span class="text-sm font-medium text-gray-900 dark:text-gray-100 mr-2" { "Tech: " }
+                @for tech in chips {
+                    button class=(chip_classes(filter.tech.contains(&tech))) hx-get=(href(&filter.toggle_tech(tech))) hx-target=(id(target_id)) {
+                        (tech)
+                    }
+                }
+                @if !filter.tech.is_empty() {
+                    button class="text-violet-600 dark:text-violet-400 hover:underline text-xs" hx-get=(href(&ProjectFilter { tech: vec![], ..filter.clone() })) hx-target=(id(target_id)) {
+                        "Clear"
+                    }
+                }
+            }
+            div class="flex flex-wrap items-center gap-2" {
+                span class="text-sm font-medium text-gray-900 dark:text-gray-100 mr-2" { "Sort: " }
+                @for sort in ProjectSort::iter() {
+                    button class=(chip_classes(filter.sort == sort)) hx-get=(href(&filter.with_sort(sort))) hx-target=(id(target_id)) {
+                        (sort.title())
+                    }
+                }
+            }
         }
     }
 }
@@ -390,8 +1199,11 @@ fn project_card_markup(project: &Project) -> Markup {
                 header class="mb-4" {
                     div class="flex items-start justify-between mb-2" {
                         h3 class="text-xl font-semibold text-primary" {
-                            (project.title)
+                            a href=(project.url()) class="hover:underline" {
+                                (project.title)
+                            }
                         }
+                        (project_status_markup(project))
                     }
                 }
 
@@ -403,26 +1215,274 @@ fn project_card_markup(project: &Project) -> Markup {
                     h4 class="text-sm font-medium text-gray-900 dark:text-gray-100 mb-2" {
                     "Tech Stack: "
                     }
-                    div class="flex flex-wrap gap-2" {
-                        @for tech in &project.tech_stack {
-                            span class="bg-violet-100 dark:bg-violet-900/30 text-violet-800 dark:text-violet-300 px-2 py-1 rounded text-xs" {
-                                (tech)
-                            }
-                        }
-                    }
+                    (tech_stack_markup(&project.tech_stack))
+                }
+
+                @if let Some(metadata) = project.repo_metadata() {
+                    (repo_metadata_markup(metadata))
+                }
+            }
+
+            (project_links_markup(project))
+        }
+    }
+}
+
+fn project_status_markup(project: &Project) -> Markup {
+    html! {
+        @if let Some(status) = project.status {
+            span class=(format!("shrink-0 px-2 py-1 rounded text-xs {}", status.classes())) title=[project.formatted_lifespan()] {
+                (status.title())
+            }
+        }
+    }
+}
+
+fn repo_metadata_markup(metadata: &RepoMetadata) -> Markup {
+    html! {
+        div class="flex flex-wrap gap-x-3 gap-y-1 mb-4 text-xs text-gray-600 dark:text-gray-400" {
+            span title="GitHub stars" { (format!("★ {}", metadata.stars)) }
+            @if let Some(language) = metadata.language {
+                span { (language) }
+            }
+            @if let Some(license) = metadata.license {
+                span { (license) }
+            }
+            @if let Some(last_commit) = metadata.last_commit {
+                span {
+                    "Last commit "
+                    time dateTime=(last_commit) { (last_commit) }
+                }
+            }
+        }
+    }
+}
+
+fn tech_stack_markup(tech_stack: &[tag::Tag]) -> Markup {
+    html! {
+        div class="flex flex-wrap gap-2" {
+            @for tech in tech_stack {
+                span class="bg-violet-100 dark:bg-violet-900/30 text-violet-800 dark:text-violet-300 px-2 py-1 rounded text-xs" {
+                    (tech)
+                }
+            }
+        }
+    }
+}
+
+fn project_links_markup(project: &Project) -> Markup {
+    html! {
+        div class="flex gap-3" {
+            @if let Some(github_url) = &project.github_url {
+                a href=(github_url) target="_blank" rel="noopener noreferrer" class="text-violet-600 dark:text-violet-400 hover:underline font-medium text-sm" {
+                    "View Code →"
                 }
             }
 
-            div class="flex gap-3" {
-                @if let Some(github_url) = &project.github_url {
-                    a href=(github_url) target="_blank" rel="noopener noreferrer" class="text-violet-600 dark:text-violet-400 hover:underline font-medium text-sm" {
-                        "View Code →"
+            @if let Some(try_it_url) = &project.try_it_url {
+                a href=(try_it_url) target="_blank" rel="noopener noreferrer" class="text-violet-600 dark:text-violet-400 hover:underline font-medium text-sm" {
+                    "Try It →"
+                }
+            }
+        }
+    }
+}
+
+fn project_detail_page_markup(project: &Project) -> Markup {
+    let posts = project.posts().collect::<Vec<_>>();
+    html! {
+        html {
+            (head(project.title))
+            body {
+                div {
+                    div class="container mx-auto px-4 py-4" {
+                        (navbar())
+                    }
+
+                    article class="max-w-4xl mx-auto px-4 py-8" {
+                        header class="mb-8 space-y-4" {
+                            h1 class="text-3xl md:text-4xl font-bold text-violet-900/50 dark:text-violet-300" {
+                                (project.title)
+                            }
+                            div class="flex flex-wrap items-center gap-4 text-sm text-gray-600 dark:text-gray-400" {
+                                (project_status_markup(project))
+                                @if let Some(lifespan) = project.formatted_lifespan() {
+                                    span { (lifespan) }
+                                }
+                            }
+                            p class="text-gray-700 dark:text-gray-300" {
+                                (project.description)
+                            }
+                            (tech_stack_markup(&project.tech_stack))
+                            (project_links_markup(project))
+                        }
+
+                        @if let Some(writeup) = project.writeup {
+                            div class="prose prose-lg dark:prose-invert max-w-none" {
+                                (PreEscaped(markdown_to_html(writeup)))
+                            }
+                        }
+
+                        @if !project.screenshots.is_empty() {
+                            section class="mt-8" {
+                                h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" { "Screenshots" }
+                                div class="grid gap-6 md:grid-cols-2" {
+                                    @for screenshot in &project.screenshots {
+                                        a href=(screenshot.src) target="_blank" {
+                                            img src=(screenshot.src) alt=(screenshot.alt) class="rounded-lg shadow-md" {};
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    div class="container mx-auto px-4 pb-8" {
+                        @if !posts.is_empty() {
+                            section class="mb-8" {
+                                h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" {
+                                    (format!("Posts About {}", project.title))
+                                }
+                                div class="grid gap-6 md:grid-cols-2 lg:grid-cols-3" {
+                                    @for (index, p) in &posts {
+                                        (post_card_markup(*index, p))
+                                    }
+                                }
+                            }
+                        }
+
+                        a href=(format!("/projects/{}", project.category)) class="text-violet-600 dark:text-violet-400 hover:underline" {
+                            "← Back to Projects"
+                        }
                     }
                 }
+            }
+        }
+    }
+}
+
+/// something that happened to a project, for the project timeline
+enum TimelineEvent {
+    Started(&'static Project),
+    Ended(&'static Project),
+    /// a post about at least one project, by index into `POSTS`
+    Post(usize, Vec<&'static Project>),
+}
+
+impl TimelineEvent {
+    fn date(&self) -> NaiveDate {
+        match self {
+            TimelineEvent::Started(project) => project.started.unwrap_or_default(),
+            TimelineEvent::Ended(project) => project.ended.unwrap_or_default(),
+            TimelineEvent::Post(index, _) => POSTS[*index].date,
+        }
+    }
+}
+
+/// every dated project start and end, and every post about a project, newest first
+fn timeline_events() -> Vec<TimelineEvent> {
+    let mut events = vec![];
+    for project in PROJECTS.iter() {
+        if project.started.is_some() {
+            events.push(TimelineEvent::Started(project));
+        }
+        if project.ended.is_some() {
+            events.push(TimelineEvent::Ended(project));
+        }
+    }
+    for (index, _) in POSTS.iter().enumerate() {
+        let projects = PROJECTS
+            .iter()
+            .filter(|project| project.posts().any(|(i, _)| i == index))
+            .collect::<Vec<_>>();
+        if !projects.is_empty() {
+            events.push(TimelineEvent::Post(index, projects));
+        }
+    }
+    events.sort_by_key(|event| Reverse(event.date()));
+    events
+}
+
+fn project_timeline_page_markup() -> Markup {
+    let undated = PROJECTS
+        .iter()
+        .filter(|p| p.started.is_none())
+        .collect::<Vec<_>>();
+    let link_classes = "text-violet-600 dark:text-violet-400 hover:underline font-medium";
+
+    html! {
+        html {
+            (head("Project Timeline"))
+            body {
+                div {
+                    div class="container mx-auto px-4 py-4" {
+                        (navbar())
+                    }
+
+                    div class="max-w-4xl mx-auto px-4 py-8" {
+                        h1 class="text-3xl md:text-4xl font-bold text-violet-900/50 dark:text-violet-300 mb-8" {
+                            "Project Timeline"
+                        }
+
+                        ol class="relative border-l-2 border-violet-300 dark:border-violet-700 space-y-6" {
+                            @for event in TIMELINE.iter() {
+                                li class="ml-6" {
+                                    span class="absolute -left-[7px] mt-1.5 h-3 w-3 rounded-full bg-purple-900/75" {}
+                                    time class="text-sm text-gray-600 dark:text-gray-400" dateTime=(event.date()) {
+                                        (event.date())
+                                    }
+                                    @match event {
+                                        TimelineEvent::Started(project) => {
+                                            div class="flex items-center gap-2" {
+                                                p class="text-gray-900 dark:text-gray-100" {
+                                                    "Started "
+                                                    a href=(project.url()) class=(link_classes) { (project.title) }
+                                                }
+                                                (project_status_markup(project))
+                                            }
+                                            p class="text-sm text-gray-700 dark:text-gray-300" { (project.description) }
+                                        }
+                                        TimelineEvent::Ended(project) => {
+                                            p class="text-gray-900 dark:text-gray-100" {
+                                                "Wrapped up "
+                                                a href=(project.url()) class=(link_classes) { (project.title) }
+                                            }
+                                        }
+                                        TimelineEvent::Post(index, projects) => {
+                                            p class="text-gray-900 dark:text-gray-100" {
+                                                "Posted "
+                                                a href=(format!("/posts/{}", index)) class=(link_classes) { (POSTS[*index].title) }
+                                            }
+                                            div class="flex flex-wrap gap-2 mt-1" {
+                                                @for project in projects {
+                                                    a href=(project.url()) class="bg-violet-100 dark:bg-violet-900/30 text-violet-800 dark:text-violet-300 px-2 py-1 rounded text-xs hover:underline" {
+                                                        (project.title)
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
 
-                @if let Some(try_it_url) = &project.try_it_url {
-                    a href=(try_it_url) target="_blank" rel="noopener noreferrer" class="text-violet-600 dark:text-violet-400 hover:underline font-medium text-sm" {
-                        "Try It →"
+                        @if !undated.is_empty() {
+                            section class="mt-12" {
+                                h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" { "Undated" }
+                                ul class="space-y-2" {
+                                    @for project in undated {
+                                        li class="flex items-center gap-2" {
+                                            a href=(project.url()) class=(link_classes) { (project.title) }
+                                            (project_status_markup(project))
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        a href="/projects" class="inline-block mt-8 text-violet-600 dark:text-violet-400 hover:underline" {
+                            "← Back to Projects"
+                        }
                     }
                 }
             }
@@ -441,6 +1501,16 @@ fn post_article_markup(p: &Post) -> Markup {
                     time dateTime=(p.date) {
                         (p.formatted_date())
                     }
+                    span { (p.formatted_reading_time()) }
+                    @if let Some((index, _)) = find_post(p.id) {
+                        a href=(format!("/posts/{}/{}.pdf", index, p.id)) class="hover:underline" { "PDF" }
+                    }
+                    @if let Some(updated) = p.updated() {
+                        span {
+                            "Updated "
+                            time dateTime=(updated) { (updated) }
+                        }
+                    }
                     div class="flex gap-2" {
                         @for tag in &p.tags {
                             span class="bg-violet-100 dark:bg-violet-900/30 text-violet-800 dark:text-violet-300 px-2 py-1 rounded text-xs" {
@@ -451,9 +1521,62 @@ fn post_article_markup(p: &Post) -> Markup {
                 }
             }
 
+            (post_series_markup(p))
+
             div class="prose prose-lg prose-footnotes:flex prose-footnotes:items-start prose-footnotes:inline-flex dark:prose-invert max-w-none" {
                 (p.content())
             }
+
+            (post_revisions_markup(p))
+        }
+    }
+}
+
+fn post_series_markup(p: &Post) -> Markup {
+    html! {
+        @if let Some((series, part)) = p.series() {
+            aside class="mb-8 p-4 border-l-4 border-violet-300 dark:border-violet-700 bg-black/5 dark:bg-white/5 rounded-r-md" {
+                p class="text-sm text-gray-700 dark:text-gray-300 mb-2" {
+                    (format!("Part {} of {} in ", part, series.post_ids.len()))
+                    a href=(series.url()) class="text-violet-600 dark:text-violet-400 hover:underline font-medium" {
+                        (series.title)
+                    }
+                }
+                ol class="list-decimal list-inside text-sm space-y-1" {
+                    @for (index, series_post) in series.posts() {
+                        li {
+                            @if series_post.id == p.id {
+                                span class="font-semibold text-gray-900 dark:text-gray-100" { (series_post.title) }
+                            } @else {
+                                a href=(&format!("/posts/{}", index)) class="text-violet-600 dark:text-violet-400 hover:underline" {
+                                    (series_post.title)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn post_revisions_markup(p: &Post) -> Markup {
+    html! {
+        @if !p.revisions().is_empty() {
+            details class="mt-8 text-sm text-gray-600 dark:text-gray-400" {
+                summary class="cursor-pointer" { "Revision history" }
+                ul class="mt-2 space-y-1" {
+                    @for revision in p.revisions() {
+                        li class="flex gap-4" {
+                            time dateTime=(revision.date) { (revision.date) }
+                            a href=(revision.diff_url()) target="_blank" rel="noopener noreferrer" class="font-mono text-violet-600 dark:text-violet-400 hover:underline" {
+                                (revision.short_sha())
+                            }
+                            span { (revision.summary) }
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -464,8 +1587,7 @@ fn post_linked_list_markup(post: &Post) -> Markup {
         .enumerate()
         .find(|(_, p)| p.id == post.id)
         .map(|i| i.0)
-        .map(|d| d.checked_sub(1))
-        .flatten();
+        .and_then(|d| d.checked_sub(1));
 
     let next_sequence_number = POSTS
         .iter()
@@ -473,8 +1595,7 @@ fn post_linked_list_markup(post: &Post) -> Markup {
         .find(|(_, p)| p.id == post.id)
         .map(|i| i.0)
         .filter(|d| *d < POSTS.len() - 1)
-        .map(|d| d.checked_add(1))
-        .flatten();
+        .and_then(|d| d.checked_add(1));
 
     let prev_post_opt = previous_sequence_number.map(|i| &POSTS[i]);
     let next_post_opt = next_sequence_number.map(|i| &POSTS[i]);
@@ -531,7 +1652,8 @@ fn post_linked_list_markup(post: &Post) -> Markup {
     }
 }
 
-fn post_page_markup(post: &Post) -> Markup {
+/// `comments` are the post's approved comments, or `None` if comments are disabled
+fn post_page_markup(post: &Post, comments: Option<&[comments::Comment]>) -> Markup {
     html! {
         html {
             (head(post.title))
@@ -543,6 +1665,10 @@ fn post_page_markup(post: &Post) -> Markup {
 
                     (post_article_markup(post))
 
+                    @if let Some(comments) = comments {
+                        (post_comments_markup(post, comments))
+                    }
+
                     div class="container mx-auto px-4 pb-8" {
                         (post_linked_list_markup(post))
 
@@ -550,6 +1676,147 @@ fn post_page_markup(post: &Post) -> Markup {
                             "← Back to Posts"
                         }
                     }
+
+                    (post_related_markup(post))
+                }
+            }
+        }
+    }
+}
+
+fn post_comments_markup(post: &Post, approved: &[comments::Comment]) -> Markup {
+    let Some((index, _)) = find_post(post.id) else {
+        return html! {};
+    };
+    let action = format!("/posts/{}/{}/comments", index, post.id);
+    let mut replies: HashMap<Option<i64>, Vec<&comments::Comment>> = HashMap::new();
+    for comment in approved {
+        replies.entry(comment.parent_id).or_default().push(comment);
+    }
+
+    html! {
+        section id="comments" class="max-w-4xl mx-auto px-4 pb-8 print:hidden" {
+            h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" { "Comments" }
+            @if approved.is_empty() {
+                p class="text-gray-600 dark:text-gray-400 mb-4" { "No comments yet." }
+            }
+            (comment_thread_markup(&replies, None, 0, &action))
+            h3 class="text-lg font-semibold text-violet-900/50 dark:text-violet-300 mt-8 mb-2" { "Leave a comment" }
+            (comment_form_markup(&action, None, "", "", None))
+        }
+    }
+}
+
+fn comment_thread_markup(
+    replies: &HashMap<Option<i64>, Vec<&comments::Comment>>,
+    parent: Option<i64>,
+    depth: usize,
+    action: &str,
+) -> Markup {
+    let Some(thread) = replies.get(&parent) else {
+        return html! {};
+    };
+    let indent = if depth > 0 && depth <= MAX_COMMENT_INDENT {
+        "ml-4 pl-4 border-l-2 border-violet-200 dark:border-violet-800"
+    } else {
+        ""
+    };
+    html! {
+        ol class=(format!("space-y-4 {indent}")) {
+            @for comment in thread {
+                li id=(format!("comment-{}", comment.id)) {
+                    div class="flex items-center gap-2 text-sm text-gray-600 dark:text-gray-400" {
+                        span class="font-semibold text-gray-900 dark:text-gray-100" { (comment.author) }
+                        time dateTime=(comment.created_at.to_rfc3339()) {
+                            (comment.created_at.format("%B %-d, %Y"))
+                        }
+                    }
+                    div class="prose dark:prose-invert max-w-none" {
+                        (PreEscaped(comments::render(&comment.body)))
+                    }
+                    details class="mt-1 mb-2 text-sm" {
+                        summary class="cursor-pointer text-violet-600 dark:text-violet-400 hover:underline" { "Reply" }
+                        (comment_form_markup(action, Some(comment.id), "", "", None))
+                    }
+                    (comment_thread_markup(replies, Some(comment.id), depth + 1, action))
+                }
+            }
+        }
+    }
+}
+
+/// The comment form, which swaps itself for the response when submitted.
+/// `message` explains why a previous submission of `author` and `body` failed.
+fn comment_form_markup(
+    action: &str,
+    parent: Option<i64>,
+    author: &str,
+    body: &str,
+    message: Option<&str>,
+) -> Markup {
+    let field_id = |name: &str| match parent {
+        Some(parent) => format!("comment-{name}-{parent}"),
+        None => format!("comment-{name}"),
+    };
+    let input_classes = "w-full rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2";
+    html! {
+        form method="post" action=(action) hx-post=(action) hx-swap="outerHTML" class="space-y-2 mt-2" {
+            @if let Some(message) = message {
+                p role="alert" class="text-red-700 dark:text-red-400" { (message) }
+            }
+            @if let Some(parent) = parent {
+                input type="hidden" name="parent" value=(parent);
+            }
+            // left empty by people, who can't see it, and filled in by bots
+            div class="hidden" aria-hidden="true" {
+                label for=(field_id("website")) { "Website" }
+                input id=(field_id("website")) name="website" type="text" tabindex="-1" autocomplete="off";
+            }
+            div {
+                label for=(field_id("author")) class="block text-sm font-medium" { "Name" }
+                input id=(field_id("author")) name="author" type="text" required maxlength=(comments::MAX_AUTHOR_LENGTH) value=(author) class=(input_classes);
+            }
+            div {
+                label for=(field_id("body")) class="block text-sm font-medium" {
+                    "Comment "
+                    span class="text-gray-600 dark:text-gray-400" { "(markdown: emphasis, code, lists, quotes, and links)" }
+                }
+                textarea id=(field_id("body")) name="body" rows="4" required maxlength=(comments::MAX_BODY_LENGTH) class=(input_classes) { (body) }
+            }
+            button type="submit" class="bg-violet-600 hover:bg-violet-700 text-white font-medium px-4 py-2 rounded" {
+                "Submit"
+            }
+        }
+    }
+}
+
+fn comment_submitted_markup() -> Markup {
+    html! {
+        p role="status" class="mt-2 text-gray-700 dark:text-gray-300" {
+            "Thanks! Your comment will appear once it has been approved."
+        }
+    }
+}
+
+fn post_related_markup(post: &Post) -> Markup {
+    let related = post.related();
+    html! {
+        div class="container mx-auto px-4 pb-8 space-y-8" {
+            @if !related.posts.is_empty() {
+                section {
+                    h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" { "Related Posts" }
+                    div class="grid gap-6 md:grid-cols-2 lg:grid-cols-3" {
+                        @for index in &related.posts {
+                            (post_card_markup(*index, &POSTS[*index]))
+                        }
+                    }
+                }
+            }
+
+            @if !related.projects.is_empty() {
+                section {
+                    h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300" { "Related Projects" }
+                    (project_grid_markup(related.projects.iter().copied()))
                 }
             }
         }
@@ -577,6 +1844,10 @@ fn post_card_markup(index: usize, p: &Post) -> Markup {
                 }
                 div class="flex flex-wrap items-center gap-4 text-sm text-gray-600 dark:text-gray-400" {
                     time dateTime=(p.date){(p.formatted_date())}
+                    span { (p.formatted_reading_time()) }
+                    @if let Some((index, _)) = find_post(p.id) {
+                        a href=(format!("/posts/{}/{}.pdf", index, p.id)) class="hover:underline" { "PDF" }
+                    }
                     div class="flex gap-2" {
                         @for tag in &p.tags {
                             span class="bg-violet-100 dark:bg-violet-900/30 text-violet-800 dark:text-violet-300 px-2 py-1 rounded text-xs" {
@@ -598,77 +1869,26 @@ fn post_card_markup(index: usize, p: &Post) -> Markup {
     }
 }
 
-// endpoint handlers
-
-async fn get_projects() -> Response {
-    project_page_markup(ProjectCategory::Production).into_response()
-}
-
-async fn get_project_tabs(Path(tab): Path<String>, headers: HeaderMap) -> Response {
-    let category = match ProjectCategory::from_str(&tab) {
-        Ok(c) => c,
-        Err(_) => return Redirect::permanent("/projects").into_response(),
-    };
-
-    match headers.get("HX-Request") {
-        Some(_) => {
-            let mut response = project_tabs_markup(category).into_response();
-
-            let headers = response.headers_mut();
-            headers.insert(
-                "HX-Push-Url",
-                HeaderValue::from_str(&format!("/projects/{category}")).unwrap(),
-            );
-
-            response
-        }
-        None => project_page_markup(category).into_response(),
-    }
-}
-
-async fn get_post_by_index(Path(desc): Path<String>) -> Result<Redirect, StatusCode> {
-    match usize::from_str(&desc) {
-        Ok(index) => {
-            if index >= POSTS.len() {
-                return Err(StatusCode::NOT_FOUND);
-            }
-            let post = &POSTS[index];
-            Ok(Redirect::permanent(&format!(
-                "/posts/{}/{}",
-                index, post.id
-            )))
-        }
-        Err(_) => {
-            // not an int, could be a post id
-            match &POSTS.iter().enumerate().find(|(_, p)| p.id == desc) {
-                Some((index, post)) => Ok(Redirect::permanent(&format!(
-                    "/posts/{}/{}",
-                    index, post.id
-                ))),
-                None => Err(StatusCode::NOT_FOUND),
-            }
-        }
-    }
-}
-
-async fn get_post_by_index_and_id(Path((index, id)): Path<(usize, String)>) -> Response {
-    let post = &POSTS[index];
-    if post.id != id {
-        return Redirect::permanent(&format!("/posts/{}/{}", index, post.id)).into_response();
-    }
-    post_page_markup(post).into_response()
-}
-
-async fn get_posts() -> Markup {
+fn series_page_markup(series: &Series) -> Markup {
     html! {
         html {
-            (head("Posts"))
+            (head(series.title))
             body {
                 div {
                     div class="container mx-auto px-4 py-4" {
                         (navbar())
-                        div class="mt-8" {
-                            (posts_list_markup(&POSTS))
+                        header class="max-w-4xl mx-auto mt-8 mb-8" {
+                            h1 class="text-3xl md:text-4xl font-bold text-violet-900/50 dark:text-violet-300 mb-4" {
+                                (series.title)
+                            }
+                            p class="text-gray-700 dark:text-gray-300" {
+                                (series.description)
+                            }
+                        }
+                        div class="grid gap-6 md:gap-8" {
+                            @for (index, p) in series.posts() {
+                                (post_card_markup(index, p))
+                            }
                         }
                     }
                 }
@@ -677,34 +1897,710 @@ async fn get_posts() -> Markup {
     }
 }
 
-async fn get_index() -> Markup {
+fn resume_page_markup() -> Markup {
+    let section_heading =
+        "text-2xl font-semibold text-violet-900/50 dark:text-violet-300 print:text-black mb-4";
+    let link_classes = "text-violet-600 dark:text-violet-400 hover:underline";
+
     html! {
-        (head("Ryan Geary"))
-        body {
-            div class="container mx-auto px-4 flex h-screen" {
-                div class="m-auto" {
-                    h1 class="font-bold underline p-4 text-primary" {
-                        span class="text-4xl md:text-6xl lg:text-8xl" {
-                            "Ryan Geary"
-                        }
+        html {
+            (head(&format!("{} - Résumé", RESUME.name)))
+            body {
+                div {
+                    div class="container mx-auto px-4 py-4" {
+                        (navbar())
                     }
 
-                    p class="flex justify-center text-secondary" {
-                        "Software Developer @Lyft"
+                    article class="max-w-4xl mx-auto px-4 py-8 print:py-0 space-y-10 print:space-y-6 text-gray-900 dark:text-gray-100 print:text-black" {
+                        header class="space-y-2" {
+                            h1 class="text-3xl md:text-4xl font-bold text-violet-900/50 dark:text-violet-300 print:text-black" {
+                                (RESUME.name)
+                            }
+                            p class="text-lg text-secondary print:text-black" { (RESUME.label) }
+                            @if let Some(summary) = RESUME.summary {
+                                p class="text-gray-700 dark:text-gray-300 print:text-black" { (summary) }
+                            }
+                            ul class="flex flex-wrap gap-x-4 gap-y-1 text-sm" {
+                                li { a href=(SITE_URL) class=(link_classes) { (SITE_URL.trim_start_matches("https://")) } }
+                                @for profile in &RESUME.profiles {
+                                    li {
+                                        (profile.network) ": "
+                                        a href=(profile.url) target="_blank" class=(link_classes) { (profile.username) }
+                                    }
+                                }
+                            }
+                            p class="text-sm print:hidden" {
+                                a href="/resume.pdf" class=(link_classes) { "PDF" }
+                                " · "
+                                a href="/resume.json" class=(link_classes) { "JSON Resume" }
+                            }
+                        }
+
+                        @if !RESUME.work.is_empty() {
+                            section {
+                                h2 class=(section_heading) { "Experience" }
+                                div class="space-y-6" {
+                                    @for work in &RESUME.work {
+                                        div class="break-inside-avoid" {
+                                            div class="flex flex-wrap justify-between gap-x-4" {
+                                                h3 class="text-lg font-semibold" {
+                                                    (work.position)
+                                                    @if let Some(organization) = work.organization {
+                                                        ", "
+                                                        @if let Some(url) = work.url {
+                                                            a href=(url) target="_blank" class=(link_classes) { (organization) }
+                                                        } @else {
+                                                            (organization)
+                                                        }
+                                                    }
+                                                }
+                                                @if let Some(dates) = formatted_date_range(work.started, work.ended, "present") {
+                                                    span class="text-sm text-gray-600 dark:text-gray-400 print:text-black" { (dates) }
+                                                }
+                                            }
+                                            @if let Some(summary) = work.summary {
+                                                p class="text-gray-700 dark:text-gray-300 print:text-black" { (summary) }
+                                            }
+                                            @if !work.highlights.is_empty() {
+                                                ul class="list-disc ml-6 mt-2 space-y-1 text-gray-700 dark:text-gray-300 print:text-black" {
+                                                    @for highlight in &work.highlights {
+                                                        li { (highlight) }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        @if !RESUME.education.is_empty() {
+                            section {
+                                h2 class=(section_heading) { "Education" }
+                                div class="space-y-4" {
+                                    @for education in &RESUME.education {
+                                        div class="flex flex-wrap justify-between gap-x-4 break-inside-avoid" {
+                                            div {
+                                                h3 class="text-lg font-semibold" {
+                                                    @if let Some(url) = education.url {
+                                                        a href=(url) target="_blank" class=(link_classes) { (education.institution) }
+                                                    } @else {
+                                                        (education.institution)
+                                                    }
+                                                }
+                                                p class="text-gray-700 dark:text-gray-300 print:text-black" {
+                                                    (education.study_type) ", " (education.area)
+                                                }
+                                            }
+                                            @if let Some(dates) = formatted_date_range(education.started, education.ended, "present") {
+                                                span class="text-sm text-gray-600 dark:text-gray-400 print:text-black" { (dates) }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        @if !RESUME.skills.is_empty() {
+                            section class="break-inside-avoid" {
+                                h2 class=(section_heading) { "Skills" }
+                                dl class="grid grid-cols-[max-content_1fr] gap-x-4 gap-y-2" {
+                                    @for group in &RESUME.skills {
+                                        dt class="font-semibold" { (group.name) }
+                                        dd { (tech_stack_markup(&group.skills)) }
+                                    }
+                                }
+                            }
+                        }
+
+                        section {
+                            h2 class=(section_heading) { "Projects" }
+                            ul class="space-y-2" {
+                                @for project in PROJECTS.iter() {
+                                    li class="break-inside-avoid" {
+                                        a href=(project.url()) class=(format!("{link_classes} font-medium")) { (project.title) }
+                                        " – "
+                                        span class="text-gray-700 dark:text-gray-300 print:text-black" { (project.description) }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn admin_page_markup(title: &str, session: &admin::AdminSession, content: Markup) -> Markup {
+    html! {
+        html {
+            (head(title))
+            body {
+                div class="container mx-auto px-4 py-4" {
+                    nav class="flex flex-wrap items-center gap-4 m-4" {
+                        a href="/admin" class="text-xl text-purple-900/50 dark:text-violet-300 hover:underline" { "Admin" }
+                        a href="/admin/stats" class="text-violet-600 dark:text-violet-400 hover:underline" { "Stats" }
+                        a href="/" class="text-violet-600 dark:text-violet-400 hover:underline" { "View site" }
+                        form method="post" action="/admin/logout" class="ml-auto" {
+                            input type="hidden" name="csrf" value=(session.csrf_token);
+                            button type="submit" class="text-violet-600 dark:text-violet-400 hover:underline" { "Log out" }
+                        }
+                    }
+                    main class="max-w-4xl mx-auto px-4 py-8 space-y-8" {
+                        (content)
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn admin_login_markup(message: Option<&str>) -> Markup {
+    html! {
+        html {
+            (head("Log in"))
+            body {
+                main class="max-w-sm mx-auto px-4 py-16" {
+                    h1 class="text-3xl font-bold text-violet-900/50 dark:text-violet-300 mb-8" { "Admin" }
+                    form method="post" action="/admin/login" class="space-y-4" {
+                        @if let Some(message) = message {
+                            p role="alert" class="text-red-700 dark:text-red-400" { (message) }
+                        }
+                        div {
+                            label for="password" class="block text-sm font-medium" { "Password" }
+                            input id="password" name="password" type="password" required autofocus autocomplete="current-password" class="w-full rounded border border-gray-300 dark:border-gray-600 bg-white dark:bg-gray-800 px-3 py-2";
+                        }
+                        button type="submit" class="bg-violet-600 hover:bg-violet-700 text-white font-medium px-4 py-2 rounded" {
+                            "Log in"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn admin_dashboard_markup(session: &admin::AdminSession) -> Markup {
+    let comment_counts = comments::store().map(|store| store.counts());
+    html! {
+        section {
+            h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" { "Site" }
+            dl class="grid grid-cols-[max-content_1fr] gap-x-8 gap-y-1" {
+                dt { "Version" }
+                dd { (BUILD_INFO.version) }
+                dt { "Commit" }
+                dd {
+                    @if let Some(url) = BUILD_INFO.commit_url() {
+                        a href=(url) class="font-mono text-violet-600 dark:text-violet-400 hover:underline" { (BUILD_INFO.commit()) }
+                    } @else {
+                        span class="font-mono" { (BUILD_INFO.commit()) }
+                    }
+                }
+                dt { "Built" }
+                dd { (BUILD_INFO.built_at().format("%Y-%m-%d %H:%M UTC")) }
+                dt { "Posts" }
+                dd { (POSTS.len()) }
+                dt { "Series" }
+                dd { (SERIES.len()) }
+                dt { "Projects" }
+                dd { (PROJECTS.len()) }
+            }
+        }
+
+        section {
+            h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300 mb-4" { "Comments" }
+            @match comment_counts {
+                None => p { "Comments are disabled; set COMMENTS_DB to enable them." },
+                Some(Err(e)) => p class="text-red-700 dark:text-red-400" { "Failed to load comments: " (e) },
+                Some(Ok(counts)) => {
+                    p class="mb-4" {
+                        @for (i, (status, count)) in counts.iter().enumerate() {
+                            @if i > 0 { ", " }
+                            (count) " " (status)
+                        }
+                    }
+                    (admin_comment_queue_markup(session))
+                },
+            }
+        }
+    }
+}
+
+/// comments awaiting moderation, each with buttons to approve or reject it
+fn admin_comment_queue_markup(session: &admin::AdminSession) -> Markup {
+    let Some(store) = comments::store() else {
+        return html! {};
+    };
+    let pending = match store.pending() {
+        Ok(pending) => pending,
+        Err(e) => {
+            return html! { p class="text-red-700 dark:text-red-400" { "Failed to load comments: " (e) } };
+        }
+    };
+    html! {
+        @if pending.is_empty() {
+            p class="text-gray-600 dark:text-gray-400" { "Nothing to moderate." }
+        }
+        ol class="space-y-4" {
+            @for comment in &pending {
+                li class="p-4 rounded-md bg-black/5 dark:bg-white/5" {
+                    div class="flex flex-wrap items-center gap-2 text-sm text-gray-600 dark:text-gray-400" {
+                        span class="font-semibold text-gray-900 dark:text-gray-100" { (comment.author) }
+                        "on"
+                        @if let Some((index, post)) = find_post(&comment.post_id) {
+                            a href=(format!("/posts/{}/{}#comments", index, post.id)) class="text-violet-600 dark:text-violet-400 hover:underline" { (post.title) }
+                        } @else {
+                            span { (comment.post_id) }
+                        }
+                        @if let Some(parent_id) = comment.parent_id {
+                            span { (format!("in reply to #{parent_id}")) }
+                        }
+                        time dateTime=(comment.created_at.to_rfc3339()) {
+                            (comment.created_at.format("%Y-%m-%d %H:%M"))
+                        }
+                    }
+                    div class="prose dark:prose-invert max-w-none my-2" {
+                        (PreEscaped(comments::render(&comment.body)))
+                    }
+                    div class="flex gap-2" {
+                        @for (action, label) in [("approve", "Approve"), ("reject", "Reject")] {
+                            form method="post" action=(format!("/admin/comments/{}/{}", comment.id, action)) hx-post=(format!("/admin/comments/{}/{}", comment.id, action)) hx-target="closest li" hx-swap="outerHTML" {
+                                input type="hidden" name="csrf" value=(session.csrf_token);
+                                button type="submit" class="bg-violet-600 hover:bg-violet-700 text-white text-sm font-medium px-3 py-1 rounded" { (label) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// page views over the last `days` days
+fn admin_stats_markup(days: u32) -> Markup {
+    let Some(store) = analytics::store() else {
+        return html! { p { "Analytics are disabled; set ANALYTICS_DB to enable them." } };
+    };
+    let since = chrono::Utc::now().date_naive() - chrono::Days::new(u64::from(days) - 1);
+    let counts = store
+        .days(since)
+        .and_then(|daily| Ok((daily, store.paths(since)?, store.referrers(since)?)));
+    let (daily, paths, referrers) = match counts {
+        Ok(counts) => counts,
+        Err(e) => {
+            return html! { p class="text-red-700 dark:text-red-400" { "Failed to load stats: " (e) } };
+        }
+    };
+    let busiest = daily.iter().map(|day| day.views).max().unwrap_or(1);
+
+    let table = |heading: &str, counts: &[analytics::Count]| {
+        html! {
+            table class="w-full text-left" {
+                thead {
+                    tr {
+                        th class="py-1" { (heading) }
+                        th class="py-1 text-right" { "Views" }
+                        th class="py-1 text-right" { "Visitors" }
+                    }
+                }
+                tbody {
+                    @for count in counts {
+                        tr class="border-t border-gray-200 dark:border-gray-700" {
+                            td class="py-1 break-all" { (count.key) }
+                            td class="py-1 text-right" { (count.views) }
+                            td class="py-1 text-right" { (count.visitors) }
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    html! {
+        section class="flex flex-wrap items-center gap-4" {
+            h2 class="text-2xl font-semibold text-violet-900/50 dark:text-violet-300" { "Page views" }
+            @for period in ADMIN_STATS_PERIODS {
+                @if period == days {
+                    span class="font-semibold" { (format!("{period} days")) }
+                } @else {
+                    a href=(format!("/admin/stats?days={period}")) class="text-violet-600 dark:text-violet-400 hover:underline" { (format!("{period} days")) }
+                }
+            }
+            a href=(format!("/admin/stats.csv?days={days}")) class="ml-auto text-violet-600 dark:text-violet-400 hover:underline" { "Export CSV" }
+        }
+
+        @if daily.is_empty() {
+            p class="text-gray-600 dark:text-gray-400" { "No page views yet." }
+        } @else {
+            section {
+                h3 class="text-lg font-semibold mb-2" { "By day" }
+                table class="w-full text-left" {
+                    thead {
+                        tr {
+                            th class="py-1" { "Day" }
+                            th class="py-1 text-right" { "Views" }
+                            th class="py-1 text-right" { "Visitors" }
+                            th class="py-1 w-1/2" {}
+                        }
+                    }
+                    tbody {
+                        @for day in &daily {
+                            tr class="border-t border-gray-200 dark:border-gray-700" {
+                                td class="py-1" { (day.key) }
+                                td class="py-1 text-right" { (day.views) }
+                                td class="py-1 text-right" { (day.visitors) }
+                                td class="py-1 pl-4" {
+                                    div class="h-3 rounded bg-violet-400" style=(format!("width: {}%", day.views * 100 / busiest)) {}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            section {
+                h3 class="text-lg font-semibold mb-2" { "Pages" }
+                (table("Path", &paths))
+            }
+            section {
+                h3 class="text-lg font-semibold mb-2" { "Referrers" }
+                @if referrers.is_empty() {
+                    p class="text-gray-600 dark:text-gray-400" { "No referred views." }
+                } @else {
+                    (table("Domain", &referrers))
+                }
+            }
+        }
+    }
+}
+
+// endpoint handlers
+
+async fn get_projects(Query(query): Query<HashMap<String, String>>) -> Response {
+    let filter = ProjectFilter::from_query(&query);
+    project_page_markup(ProjectCategory::first(), &filter).into_response()
+}
+
+async fn get_project_tabs(
+    Path(tab): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(category) = ProjectCategory::find(&tab) else {
+        return Redirect::permanent("/projects").into_response();
+    };
+    let filter = ProjectFilter::from_query(&query);
+
+    match headers.get("HX-Request") {
+        Some(_) => {
+            let mut response = project_tabs_markup(category, &filter).into_response();
+
+            let headers = response.headers_mut();
+            headers.insert(
+                "HX-Push-Url",
+                HeaderValue::from_str(&format!("/projects/{category}{}", filter.query_string()))
+                    .unwrap(),
+            );
+
+            response
+        }
+        None => project_page_markup(category, &filter).into_response(),
+    }
+}
+
+async fn get_project_timeline() -> Markup {
+    project_timeline_page_markup()
+}
+
+async fn get_project(Path((category, id)): Path<(String, String)>) -> Response {
+    let Some(project) = PROJECTS.iter().find(|p| p.id == id) else {
+        return not_found().await;
+    };
+    if project.category.slug != category {
+        return Redirect::permanent(&project.url()).into_response();
+    }
+    project_detail_page_markup(project).into_response()
+}
+
+async fn get_post_by_index(Path(desc): Path<String>) -> Result<Redirect, StatusCode> {
+    match usize::from_str(&desc) {
+        Ok(index) => {
+            if index >= POSTS.len() {
+                return Err(StatusCode::NOT_FOUND);
+            }
+            let post = &POSTS[index];
+            Ok(Redirect::permanent(&format!(
+                "/posts/{}/{}",
+                index, post.id
+            )))
+        }
+        Err(_) => {
+            // not an int, could be a post id
+            match find_post(&desc) {
+                Some((index, post)) => Ok(Redirect::permanent(&format!(
+                    "/posts/{}/{}",
+                    index, post.id
+                ))),
+                None => Err(StatusCode::NOT_FOUND),
+            }
+        }
+    }
+}
+
+async fn get_post_by_index_and_id(
+    Path((index, id)): Path<(usize, String)>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(post) = POSTS.get(index) else {
+        return not_found().await;
+    };
+
+    // an explicit extension wins over content negotiation
+    let (id, format) = match id
+        .rsplit_once('.')
+        .and_then(|(id, ext)| Some((id, PostFormat::from_extension(ext)?)))
+    {
+        Some((id, format)) => (id, format),
+        None => (id.as_str(), PostFormat::from_accept(&headers)),
+    };
+
+    if post.id != id {
+        return Redirect::permanent(&format!(
+            "/posts/{}/{}{}",
+            index,
+            post.id,
+            format.extension()
+        ))
+        .into_response();
+    }
+
+    let mut response = match format {
+        PostFormat::Html => {
+            let comments = approved_comments(post).await;
+            post_page_markup(post, comments.as_deref()).into_response()
+        }
+        PostFormat::Markdown => (
+            [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            post.content,
+        )
+            .into_response(),
+        PostFormat::PlainText => (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            post.plaintext(),
+        )
+            .into_response(),
+        PostFormat::Pdf => {
+            let url = absolute_url(&format!("/posts/{}/{}", index, post.id));
+            pdf_response(post.id, move || pdf::post(post, &url)).await
+        }
+    };
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+/// approved comments on a post, loaded off the async runtime; `None` if
+/// comments are disabled
+async fn approved_comments(post: &'static Post) -> Option<Vec<comments::Comment>> {
+    let store = comments::store()?;
+    let approved = match tokio::task::spawn_blocking(move || store.approved(post.id)).await {
+        Ok(Ok(approved)) => approved,
+        Ok(Err(e)) => {
+            tracing::error!("failed to load comments on {}: {}", post.id, e);
+            vec![]
+        }
+        Err(e) => {
+            tracing::error!("comments task for {} failed: {}", post.id, e);
+            vec![]
+        }
+    };
+    Some(approved)
+}
+
+async fn post_comment(
+    Path((index, id)): Path<(usize, String)>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let (Some(store), Some(post)) = (comments::store(), POSTS.get(index)) else {
+        return not_found().await;
+    };
+    if post.id != id {
+        return not_found().await;
+    }
+    let field = |name: &str| form.get(name).map(String::as_str).unwrap_or_default();
+    let parent = match field("parent") {
+        "" => None,
+        parent => match parent.parse() {
+            Ok(parent) => Some(parent),
+            Err(_) => return StatusCode::BAD_REQUEST.into_response(),
+        },
+    };
+
+    // the honeypot was filled in, so this is a bot; let it think it succeeded
+    let markup = if !field("website").is_empty() {
+        comment_submitted_markup()
+    } else {
+        let client = client_ip(&headers, peer);
+        let (author, body) = (field("author").to_owned(), field("body").to_owned());
+        let submitted = tokio::task::spawn_blocking(move || {
+            store.submit(post.id, parent, &author, &body, client)
+        })
+        .await;
+        let not_saved = "Sorry, your comment couldn't be saved; please try again later.";
+        let message = match submitted {
+            Ok(Ok(())) => None,
+            Ok(Err(comments::SubmitError::RateLimited)) => {
+                Some("You've left a lot of comments recently; please try again later.")
+            }
+            Ok(Err(comments::SubmitError::Invalid(message))) => Some(message),
+            Ok(Err(comments::SubmitError::Database(e))) => {
+                tracing::error!("failed to save comment on {}: {}", post.id, e);
+                Some(not_saved)
+            }
+            Err(e) => {
+                tracing::error!("comments task for {} failed: {}", post.id, e);
+                Some(not_saved)
+            }
+        };
+        match message {
+            None => comment_submitted_markup(),
+            Some(message) => {
+                let action = format!("/posts/{}/{}/comments", index, post.id);
+                comment_form_markup(
+                    &action,
+                    parent,
+                    field("author"),
+                    field("body"),
+                    Some(message),
+                )
+            }
+        }
+    };
+
+    match headers.get("HX-Request") {
+        Some(_) => markup.into_response(),
+        None => html! {
+            html {
+                (head(post.title))
+                body {
+                    div class="container mx-auto px-4 py-4" {
+                        (navbar())
+                        div class="max-w-4xl mx-auto px-4 py-8" {
+                            (markup)
+                            a href=(format!("/posts/{}/{}#comments", index, post.id)) class="inline-block mt-8 text-violet-600 dark:text-violet-400 hover:underline" {
+                                "← Back to the post"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        .into_response(),
+    }
+}
+
+async fn get_posts() -> Markup {
+    html! {
+        html {
+            (head("Posts"))
+            body {
+                div {
+                    div class="container mx-auto px-4 py-4" {
+                        (navbar())
+                        div class="mt-8" {
+                            (posts_list_markup(&POSTS))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn get_series(Path(id): Path<String>) -> Response {
+    match SERIES.iter().find(|s| s.id == id) {
+        Some(series) => series_page_markup(series).into_response(),
+        None => not_found().await,
+    }
+}
+
+async fn get_resume() -> Markup {
+    resume_page_markup()
+}
+
+async fn get_resume_json() -> Json<serde_json::Value> {
+    Json(RESUME.to_json_resume())
+}
+
+async fn get_resume_pdf() -> Response {
+    pdf_response("resume", || pdf::resume(&RESUME, &absolute_url("/resume"))).await
+}
+
+/// render a PDF off the async runtime, served inline as `{name}.pdf`
+async fn pdf_response(
+    name: &str,
+    render: impl FnOnce() -> Result<Vec<u8>, printpdf::Error> + Send + 'static,
+) -> Response {
+    match tokio::task::spawn_blocking(render).await {
+        Ok(Ok(bytes)) => (
+            [
+                (header::CONTENT_TYPE, "application/pdf".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("inline; filename=\"{name}.pdf\""),
+                ),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Ok(Err(e)) => {
+            tracing::error!("failed to render {name}.pdf: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        Err(e) => {
+            tracing::error!("failed to render {name}.pdf: {e}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_index() -> Markup {
+    html! {
+        (head(HOMEPAGE.name))
+        body {
+            div class="container mx-auto px-4 flex h-screen" {
+                div class="m-auto" {
+                    h1 class="font-bold underline p-4 text-primary" {
+                        span class="text-4xl md:text-6xl lg:text-8xl" {
+                            (HOMEPAGE.name)
+                        }
                     }
-                    p class="flex justify-center text-secondary" {
-                        "FOSS Developer"
+
+                    @for line in HOMEPAGE.tagline() {
+                        p class="flex justify-center text-secondary" {
+                            (line)
+                        }
                     }
 
-                    div class="flex justify-center" {
-                        img src="/static/headshot.jpg" alt="Ryan Geary's headshot" class="w-3xs rounded-full p-10" {};
+                    @if let Some(headshot) = &HOMEPAGE.headshot {
+                        div class="flex justify-center" {
+                            img src=(headshot.src) alt=(headshot.alt) class="w-3xs rounded-full p-10" {};
+                        }
                     }
 
-                    div class="grid grid-cols-2 grid-rows-2 gap-4" {
-                        @for b in HOMEPAGE_BUTTONS {
-                            a href=(b.href) target=(b.target.unwrap_or("_self")) class="
+                    // two buttons per row, with an odd one out taking a whole row
+                    div class="flex flex-wrap gap-4" {
+                        @for b in &HOMEPAGE.buttons {
+                            a href=(b.href) target=(b.target.unwrap_or("_self")) rel=[b.rel_me.then_some("me")] class="
                                 flex
+                                grow
+                                basis-[calc(50%-0.5rem)]
+                                items-center
                                 justify-center
+                                gap-2
                                 text-amber-200
                                 hover:text-amber-50
                                 bg-purple-900/75
@@ -717,6 +2613,9 @@ async fn get_index() -> Markup {
                                 shadow-[8px_8px_0_rgba(0,0,0,0.25)]
                                 "
                             {
+                                @if let Some(icon) = b.icon {
+                                    (icon_markup(icon))
+                                }
                                 (b.title)
                             }
                         }
@@ -728,33 +2627,243 @@ async fn get_index() -> Markup {
 }
 
 async fn get_static_file(Path(path): Path<String>) -> impl IntoResponse {
-    tracing::info!("static");
-    match Assets::get(&path) {
+    let asset = tracing::info_span!("asset lookup", path).in_scope(|| Assets::get(&path));
+    match asset {
         Some(content) => {
             let mime = mime_guess::from_path(path).first_or_octet_stream();
+            metrics::static_asset_served(content.data.len());
 
             ([(header::CONTENT_TYPE, mime.as_ref())], content.data).into_response()
         }
         None => {
-            println!(
-                "{} not found in {:?}",
-                path,
-                Assets::iter().collect::<Vec<_>>()
-            );
+            tracing::warn!(path, "static asset not found");
             not_found().await
         }
     }
 }
 
-async fn not_found() -> Response {
+async fn get_admin(session: admin::AdminSession) -> Response {
+    // the dashboard queries the comments database
+    let page = tokio::task::spawn_blocking(move || {
+        admin_page_markup("Admin", &session, admin_dashboard_markup(&session))
+    })
+    .await;
+    match page {
+        Ok(page) => no_store(page),
+        Err(e) => {
+            tracing::error!("admin dashboard task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_admin_login() -> Response {
+    if admin::auth().is_none() {
+        return not_found().await;
+    }
+    no_store(admin_login_markup(None))
+}
+
+async fn post_admin_login(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let Some(auth) = admin::auth() else {
+        return not_found().await;
+    };
+    let client = client_ip(&headers, peer);
+    let password = form.get("password").cloned().unwrap_or_default();
+    let login = tokio::task::spawn_blocking(move || auth.log_in(&password, client)).await;
+
+    let (status, message) = match login {
+        Ok(Ok(token)) => {
+            tracing::info!("admin logged in from {}", client);
+            return (
+                [(header::SET_COOKIE, admin::session_cookie(Some(&token)))],
+                Redirect::to("/admin"),
+            )
+                .into_response();
+        }
+        Ok(Err(admin::LoginError::WrongPassword)) => {
+            tracing::warn!("failed admin login from {}", client);
+            (StatusCode::UNAUTHORIZED, "Wrong password.")
+        }
+        Ok(Err(admin::LoginError::RateLimited)) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many attempts; please try again later.",
+        ),
+        Err(e) => {
+            tracing::error!("login task failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Something went wrong; please try again.",
+            )
+        }
+    };
+    (status, no_store(admin_login_markup(Some(message)))).into_response()
+}
+
+async fn post_admin_logout(
+    session: admin::AdminSession,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    if !session.verify_csrf(&form) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    if let Some(auth) = admin::auth() {
+        auth.log_out(&session);
+    }
+    (
+        [(header::SET_COOKIE, admin::session_cookie(None))],
+        Redirect::to("/admin/login"),
+    )
+        .into_response()
+}
+
+/// the `days` query parameter of the stats pages, if it is one of
+/// `ADMIN_STATS_PERIODS`
+fn admin_stats_period(query: &HashMap<String, String>) -> u32 {
+    query
+        .get("days")
+        .and_then(|days| days.parse().ok())
+        .filter(|days| ADMIN_STATS_PERIODS.contains(days))
+        .unwrap_or(ADMIN_STATS_PERIODS[1])
+}
+
+async fn get_admin_stats(
+    session: admin::AdminSession,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let days = admin_stats_period(&query);
+    // the stats are queried from the analytics database as they're rendered
+    let page = tokio::task::spawn_blocking(move || {
+        admin_page_markup("Stats", &session, admin_stats_markup(days))
+    })
+    .await;
+    match page {
+        Ok(page) => no_store(page),
+        Err(e) => {
+            tracing::error!("page view stats task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn get_admin_stats_csv(
+    _session: admin::AdminSession,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let Some(store) = analytics::store() else {
+        return not_found().await;
+    };
+    let days = admin_stats_period(&query);
+    let since = chrono::Utc::now().date_naive() - chrono::Days::new(u64::from(days) - 1);
+    match tokio::task::spawn_blocking(move || store.csv(since)).await {
+        Ok(Ok(csv)) => (
+            [
+                (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"page-views-since-{since}.csv\""),
+                ),
+                (header::CACHE_CONTROL, "no-store".to_string()),
+            ],
+            csv,
+        )
+            .into_response(),
+        Ok(Err(e)) => {
+            tracing::error!("failed to export page views: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+        Err(e) => {
+            tracing::error!("page view export task failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn post_admin_comment(
+    session: admin::AdminSession,
+    Path((id, action)): Path<(i64, String)>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    if !session.verify_csrf(&form) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(store) = comments::store() else {
+        return not_found().await;
+    };
+    let (status, done) = match action.as_str() {
+        "approve" => (comments::Status::Approved, "Approved"),
+        "reject" => (comments::Status::Rejected, "Rejected"),
+        _ => return not_found().await,
+    };
+
+    match tokio::task::spawn_blocking(move || store.set_status(id, status)).await {
+        Ok(Ok(true)) => {}
+        Ok(Ok(false)) => return not_found().await,
+        Ok(Err(e)) => {
+            tracing::error!("failed to moderate comment {}: {}", id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+        Err(e) => {
+            tracing::error!("moderation task for comment {} failed: {}", id, e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    match headers.get("HX-Request") {
+        Some(_) => no_store(html! {
+            li class="p-4 text-gray-600 dark:text-gray-400" { (format!("{done} #{id}.")) }
+        }),
+        None => Redirect::to("/admin").into_response(),
+    }
+}
+
+async fn not_found() -> Response {
     (StatusCode::NOT_FOUND, "404").into_response()
 }
 
-async fn health_check() -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "status": "healthy",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-    }))
+/// only for scrapers connecting from a trusted address; anything else, including
+/// visitors coming through a trusted proxy, gets a 404
+async fn get_metrics(ConnectInfo(peer): ConnectInfo<SocketAddr>, headers: HeaderMap) -> Response {
+    if !access_log::is_trusted_direct(&headers, peer) {
+        return not_found().await;
+    }
+    (
+        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics::render(),
+    )
+        .into_response()
+}
+
+/// Log a Content-Security-Policy violation report, sent as
+/// `application/csp-report` by `report-uri` or `application/reports+json` by
+/// `report-to`.
+async fn post_csp_report(body: axum::body::Bytes) -> StatusCode {
+    // anyone can send reports, so they're only logged when asked for
+    let report = String::from_utf8_lossy(&body[..body.len().min(4096)]);
+    tracing::debug!(target: "www::csp", report = %report, "content security policy violation");
+    metrics::csp_report();
+    StatusCode::NO_CONTENT
+}
+
+async fn get_health_live() -> Json<serde_json::Value> {
+    Json(health::live())
+}
+
+async fn get_health_ready() -> Response {
+    // pings the databases
+    match tokio::task::spawn_blocking(health::ready).await {
+        Ok((true, body)) => Json(body).into_response(),
+        Ok((false, body)) => (StatusCode::SERVICE_UNAVAILABLE, Json(body)).into_response(),
+        Err(e) => {
+            tracing::error!("readiness check task failed: {}", e);
+            StatusCode::SERVICE_UNAVAILABLE.into_response()
+        }
+    }
 }
 
 // main + router
@@ -762,49 +2871,4115 @@ async fn health_check() -> Json<serde_json::Value> {
 #[tokio::main]
 async fn main() {
     // Initialize tracing
-    tracing_subscriber::registry()
+    let json_logs = std::env::var("LOG_FORMAT").is_ok_and(|format| format == "json");
+    let logging = tracing_subscriber::registry()
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-                "grocery_list_backend=debug,tower_http=debug,axum::rejection=trace".into()
-            }),
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "www=info,tower_http=debug,axum::rejection=trace".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+        .with((!json_logs).then(tracing_subscriber::fmt::layer))
+        .with(json_logs.then(|| {
+            tracing_subscriber::fmt::layer()
+                .json()
+                .flatten_event(true)
+                .with_span_list(false)
+        }));
+
+    #[cfg(feature = "otel")]
+    let (logging, tracer_provider) = match otel::provider_from_env() {
+        Ok(provider) => (logging.with(provider.as_ref().map(otel::layer)), provider),
+        Err(e) => {
+            logging.init();
+            tracing::error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    logging.init();
+
+    if let Err(e) = access_log::init_from_env() {
+        tracing::error!("{}", e);
+        std::process::exit(1);
+    }
+
+    #[cfg(feature = "github-metadata-fetch")]
+    if std::env::args().nth(1).as_deref() == Some("fetch-github-metadata") {
+        let path = std::env::args()
+            .nth(2)
+            .unwrap_or_else(|| "github.json".to_string());
+        if let Err(e) = github::fetch_snapshot(std::path::Path::new(&path)) {
+            tracing::error!("failed to fetch github metadata: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("comments") {
+        let args: Vec<String> = std::env::args().skip(2).collect();
+        if let Err(e) = comments::run_subcommand(&args) {
+            tracing::error!("comments: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if std::env::args().nth(1).as_deref() == Some("hash-password") {
+        match admin::hash_password() {
+            Ok(hash) => println!("{hash}"),
+            Err(e) => {
+                tracing::error!("hash-password: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Err(e) = admin::init_from_env() {
+        tracing::error!("{}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(path) = comments::path_from_env()
+        && let Err(e) = comments::init(&path)
+    {
+        tracing::error!("failed to open comments database {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+
+    if let Some(path) = analytics::path_from_env()
+        && let Err(e) = analytics::init(&path)
+    {
+        tracing::error!(
+            "failed to open analytics database {}: {}",
+            path.display(),
+            e
+        );
+        std::process::exit(1);
+    }
+
+    health::start();
+    tokio::task::spawn_blocking(health::run_self_checks);
 
     // Build our application
     let app = Router::new()
         .route("/static/{file}", get(get_static_file))
         .route("/", get(get_index))
         .route("/projects", get(get_projects))
+        .route("/projects/timeline", get(get_project_timeline))
         .route("/projects/{tab}", get(get_project_tabs))
+        .route("/projects/{category}/{id}", get(get_project))
         .route("/posts/{index}", get(get_post_by_index))
         .route("/posts/{index}/{id}", get(get_post_by_index_and_id))
+        .route("/posts/{index}/{id}/comments", post(post_comment))
         .route("/posts", get(get_posts))
-        .route("/health", get(health_check))
-        .layer(TraceLayer::new_for_http());
+        .route("/series/{id}", get(get_series))
+        .route("/resume", get(get_resume))
+        .route("/resume.json", get(get_resume_json))
+        .route("/resume.pdf", get(get_resume_pdf))
+        .route("/admin", get(get_admin))
+        .route("/admin/login", get(get_admin_login).post(post_admin_login))
+        .route("/admin/logout", post(post_admin_logout))
+        .route("/admin/stats", get(get_admin_stats))
+        .route("/admin/stats.csv", get(get_admin_stats_csv))
+        .route("/admin/comments/{id}/{action}", post(post_admin_comment))
+        .route("/health", get(get_health_live))
+        .route("/health/live", get(get_health_live))
+        .route("/health/ready", get(get_health_ready))
+        .route("/metrics", get(get_metrics))
+        .route(
+            security::CSP_REPORT_PATH,
+            post(post_csp_report).layer(DefaultBodyLimit::max(64 * 1024)),
+        )
+        .layer(middleware::from_fn(analytics::track))
+        .layer(middleware::from_fn(rate_limit::limit))
+        .layer(middleware::from_fn(metrics::track))
+        .layer(middleware::from_fn(security::headers_layer))
+        .layer(TraceLayer::new_for_http().make_span_with(access_log::span))
+        .layer(middleware::from_fn(access_log::track));
+
+    // Run it on port 3000, or $PORT
+    let port = std::env::var("PORT")
+        .ok()
+        .and_then(|port| port.parse::<u16>().ok())
+        .unwrap_or(3000);
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port))
+        .await
+        .unwrap();
+
+    // every server stops accepting connections on the same signal
+    let (shutdown_tx, shutdown_rx) = watch::channel(());
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        let _ = shutdown_tx.send(());
+    });
 
-    // Run it on localhost:3000
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_requested(shutdown_rx.clone()));
 
-    let server =
-        axum::serve(listener, app.into_make_service()).with_graceful_shutdown(shutdown_signal());
+    let mut other_servers = JoinSet::new();
+
+    if let Some(config) = gopher::Config::from_env() {
+        let shutdown = shutdown_requested(shutdown_rx.clone());
+        other_servers.spawn(async move {
+            if let Err(e) = gopher::serve(config, shutdown).await {
+                tracing::error!("gopher server error: {}", e);
+            }
+        });
+    }
+
+    if let Some(addr) = finger::addr_from_env() {
+        let shutdown = shutdown_requested(shutdown_rx.clone());
+        other_servers.spawn(async move {
+            if let Err(e) = finger::serve(addr, shutdown).await {
+                tracing::error!("finger server error: {}", e);
+            }
+        });
+    }
+
+    if let Some(config) = gemini::Config::from_env() {
+        let shutdown = shutdown_requested(shutdown_rx.clone());
+        other_servers.spawn(async move {
+            if let Err(e) = gemini::serve(config, shutdown).await {
+                tracing::error!("gemini server error: {}", e);
+            }
+        });
+    }
 
     if let Err(e) = server.await {
         tracing::error!("server error: {}", e);
     }
+    other_servers.join_all().await;
+
+    // export any spans still buffered
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider
+        && let Err(e) = provider.shutdown()
+    {
+        tracing::error!("failed to flush traces: {}", e);
+    }
 }
 
-// utility functions
+// github metadata
 
-fn markdown_to_html(markdown: &str) -> String {
-    let parser = Parser::new_ext(markdown, Options::all());
-    let mut html_output = String::new();
-    html::push_html(&mut html_output, parser);
-    html_output
+/// The `fetch-github-metadata [path]` subcommand, which saves the github.json
+/// snapshot (default `./github.json`) read by build.rs. Set `GITHUB_TOKEN` to
+/// avoid the unauthenticated rate limit.
+#[cfg(feature = "github-metadata-fetch")]
+mod github {
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::PROJECTS;
+
+    const API_URL: &str = "https://api.github.com";
+
+    /// the format of github.json
+    #[derive(Serialize)]
+    struct Snapshot {
+        fetched_at: String,
+        repositories: BTreeMap<&'static str, Repository>,
+    }
+
+    #[derive(Serialize)]
+    struct Repository {
+        stars: u64,
+        /// yyyy-mm-dd
+        last_commit: Option<String>,
+        language: Option<String>,
+        license: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct RepoResponse {
+        stargazers_count: u64,
+        language: Option<String>,
+        license: Option<LicenseResponse>,
+    }
+
+    #[derive(Deserialize)]
+    struct LicenseResponse {
+        spdx_id: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct CommitResponse {
+        commit: CommitDetail,
+    }
+
+    #[derive(Deserialize)]
+    struct CommitDetail {
+        committer: Option<Signature>,
+    }
+
+    #[derive(Deserialize)]
+    struct Signature {
+        /// ISO 8601
+        date: String,
+    }
+
+    pub fn fetch_snapshot(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let mut repositories = BTreeMap::new();
+
+        for repo in PROJECTS.iter().filter_map(|p| p.github_repo()) {
+            tracing::info!("fetching {}", repo);
+
+            let metadata: RepoResponse = get(&format!("{API_URL}/repos/{repo}"))?;
+            let commits: Vec<CommitResponse> =
+                get(&format!("{API_URL}/repos/{repo}/commits?per_page=1"))?;
+
+            repositories.insert(
+                repo,
+                Repository {
+                    stars: metadata.stargazers_count,
+                    last_commit: commits
+                        .into_iter()
+                        .next()
+                        .and_then(|c| c.commit.committer)
+                        .and_then(|s| s.date.get(..10).map(str::to_string)),
+                    language: metadata.language,
+                    // GitHub reports licenses it can't identify as NOASSERTION
+                    license: metadata
+                        .license
+                        .and_then(|l| l.spdx_id)
+                        .filter(|id| id != "NOASSERTION"),
+                },
+            );
+        }
+
+        let snapshot = Snapshot {
+            fetched_at: chrono::Utc::now().to_rfc3339(),
+            repositories,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&snapshot)? + "\n")?;
+        tracing::info!("wrote {}", path.display());
+
+        Ok(())
+    }
+
+    fn get<T: for<'de> Deserialize<'de>>(url: &str) -> Result<T, ureq::Error> {
+        let mut request = ureq::get(url)
+            .header("User-Agent", "www-fetch-github-metadata")
+            .header("Accept", "application/vnd.github+json");
+        if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+            request = request.header("Authorization", &format!("Bearer {token}"));
+        }
+        request.call()?.body_mut().read_json()
+    }
 }
 
-fn id(s: &str) -> String {
-    format!("#{s}")
+// gemini
+
+/// A Gemini protocol (gemini://) mirror of the posts and projects, served over
+/// TLS from the same binary when `GEMINI_ADDR` (e.g. `0.0.0.0:1965`) is set.
+///
+/// The certificate and key are read from `GEMINI_CERT` and `GEMINI_KEY`
+/// (default `gemini/cert.pem` and `gemini/key.pem`). If they don't exist, a
+/// self-signed certificate for `GEMINI_HOSTNAME` is generated and saved there,
+/// so that clients' trust-on-first-use pinning survives restarts.
+mod gemini {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinSet;
+    use tokio_rustls::TlsAcceptor;
+    use tokio_rustls::rustls::ServerConfig;
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+    use super::{
+        HOMEPAGE, MIRRORED_PATHS, POSTS, PROJECT_CATEGORIES, Post, SITE_URL, absolute_url,
+    };
+
+    /// longest request line allowed by the spec, excluding the trailing CRLF
+    const MAX_REQUEST_LENGTH: usize = 1024;
+    /// longest a connection may take, from accepting it to closing it
+    const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+    pub struct Config {
+        addr: String,
+        hostname: String,
+        cert_path: PathBuf,
+        key_path: PathBuf,
+    }
+
+    impl Config {
+        /// `None` if the gemini server is not enabled
+        pub fn from_env() -> Option<Config> {
+            let var = |name: &str, default: &str| {
+                std::env::var(name).unwrap_or_else(|_| default.to_string())
+            };
+
+            Some(Config {
+                addr: std::env::var("GEMINI_ADDR").ok()?,
+                hostname: var("GEMINI_HOSTNAME", "ryangeary.dev"),
+                cert_path: var("GEMINI_CERT", "gemini/cert.pem").into(),
+                key_path: var("GEMINI_KEY", "gemini/key.pem").into(),
+            })
+        }
+    }
+
+    /// a gemini response, as a status code, meta line, and optional body
+    struct Response {
+        status: u8,
+        meta: String,
+        body: String,
+    }
+
+    impl Response {
+        fn success(body: String) -> Response {
+            Response {
+                status: 20,
+                meta: "text/gemini; lang=en".to_string(),
+                body,
+            }
+        }
+
+        fn redirect(path: String) -> Response {
+            Response {
+                status: 31,
+                meta: path,
+                body: String::new(),
+            }
+        }
+
+        fn not_found() -> Response {
+            Response {
+                status: 51,
+                meta: "Not found".to_string(),
+                body: String::new(),
+            }
+        }
+
+        fn bad_request() -> Response {
+            Response {
+                status: 59,
+                meta: "Bad request".to_string(),
+                body: String::new(),
+            }
+        }
+    }
+
+    /// serves until `shutdown` resolves, then waits for in-flight requests
+    pub async fn serve(config: Config, shutdown: impl Future<Output = ()>) -> std::io::Result<()> {
+        let acceptor = TlsAcceptor::from(Arc::new(tls_config(&config)?));
+        let listener = TcpListener::bind(&config.addr).await?;
+        tracing::info!("gemini server listening on {}", config.addr);
+
+        let mut connections = JoinSet::new();
+        let mut shutdown = std::pin::pin!(shutdown);
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                () = &mut shutdown => break,
+            };
+            let (stream, peer) = match accepted {
+                Ok(accepted) => accepted,
+                // e.g. out of file descriptors, which closing connections will fix
+                Err(e) => {
+                    tracing::error!("gemini accept error: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            let acceptor = acceptor.clone();
+            connections.spawn(async move {
+                let handled =
+                    tokio::time::timeout(CONNECTION_TIMEOUT, handle_connection(acceptor, stream));
+                match handled.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => tracing::debug!("gemini connection from {} failed: {}", peer, e),
+                    Err(_) => tracing::debug!("gemini connection from {} timed out", peer),
+                }
+            });
+            while connections.try_join_next().is_some() {}
+        }
+
+        // each connection is bounded by CONNECTION_TIMEOUT
+        connections.join_all().await;
+        Ok(())
+    }
+
+    fn tls_config(config: &Config) -> std::io::Result<ServerConfig> {
+        if !config.cert_path.exists() || !config.key_path.exists() {
+            tracing::info!(
+                "generating self-signed gemini certificate for {}",
+                config.hostname
+            );
+            let generated = rcgen::generate_simple_self_signed(vec![config.hostname.clone()])
+                .map_err(std::io::Error::other)?;
+            for path in [&config.cert_path, &config.key_path] {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+            }
+            std::fs::write(&config.cert_path, generated.cert.pem())?;
+            // the private key is readable by its owner only
+            std::fs::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .mode(0o600)
+                .open(&config.key_path)?
+                .write_all(generated.signing_key.serialize_pem().as_bytes())?;
+        }
+
+        let certs = CertificateDer::pem_file_iter(&config.cert_path)
+            .map_err(std::io::Error::other)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(std::io::Error::other)?;
+        let key = PrivateKeyDer::from_pem_file(&config.key_path).map_err(std::io::Error::other)?;
+
+        ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(std::io::Error::other)
+    }
+
+    async fn handle_connection(acceptor: TlsAcceptor, stream: TcpStream) -> std::io::Result<()> {
+        let mut stream = acceptor.accept(stream).await?;
+
+        let mut request = String::new();
+        let mut reader = BufReader::new((&mut stream).take(MAX_REQUEST_LENGTH as u64 + 2));
+        reader.read_line(&mut request).await?;
+
+        let response = match request.strip_suffix("\r\n") {
+            Some(url) if url.len() <= MAX_REQUEST_LENGTH => respond(url),
+            _ => Response::bad_request(),
+        };
+        tracing::info!("gemini {} {}", response.status, request.trim_end());
+
+        stream
+            .write_all(format!("{} {}\r\n", response.status, response.meta).as_bytes())
+            .await?;
+        stream.write_all(response.body.as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    fn respond(url: &str) -> Response {
+        let Some(rest) = url.strip_prefix("gemini://") else {
+            return Response::bad_request();
+        };
+        let path = rest
+            .find('/')
+            .map(|slash| &rest[slash..])
+            .unwrap_or("/")
+            .split(['?', '#'])
+            .next()
+            .unwrap_or("/");
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+
+        match segments.as_slice() {
+            [] => Response::success(index()),
+            ["posts"] => Response::success(posts()),
+            ["posts", index] => match super::find_post(index).or_else(|| {
+                let index = index.parse::<usize>().ok()?;
+                Some((index, POSTS.get(index)?))
+            }) {
+                Some((index, post)) => Response::redirect(format!("/posts/{}/{}", index, post.id)),
+                None => Response::not_found(),
+            },
+            ["posts", index, id] => match index.parse::<usize>().ok().and_then(|i| POSTS.get(i)) {
+                Some(post) if post.id == *id => Response::success(post_page(post)),
+                Some(post) => Response::redirect(format!("/posts/{}/{}", index, post.id)),
+                None => Response::not_found(),
+            },
+            ["projects"] => Response::success(projects()),
+            _ => Response::not_found(),
+        }
+    }
+
+    fn index() -> String {
+        let mut page = format!("# {}\n\n", HOMEPAGE.name);
+        for role in HOMEPAGE.tagline() {
+            page.push_str(&format!("{role}\n"));
+        }
+        page.push('\n');
+        for b in &HOMEPAGE.buttons {
+            if MIRRORED_PATHS.contains(&b.href) {
+                page.push_str(&format!("=> {} {}\n", b.href, b.title));
+            } else {
+                page.push_str(&format!("=> {} {}\n", absolute_url(b.href), b.title));
+            }
+        }
+        page.push_str(&format!("\n=> {SITE_URL} View on the web\n"));
+        page
+    }
+
+    fn posts() -> String {
+        let mut page = String::from("# Posts\n\n");
+        for (index, p) in POSTS.iter().enumerate().rev() {
+            page.push_str(&format!(
+                "=> /posts/{}/{} {} {}\n",
+                index,
+                p.id,
+                p.formatted_date(),
+                p.title
+            ));
+        }
+        page.push_str("\n=> / Home\n");
+        page
+    }
+
+    fn post_page(p: &Post) -> String {
+        let index = super::find_post(p.id)
+            .map(|(index, _)| index)
+            .unwrap_or_default();
+        let mut page = format!(
+            "# {}\n\n{} · {}\n",
+            p.title,
+            p.formatted_date(),
+            p.formatted_reading_time()
+        );
+        if let Some((series, part)) = p.series() {
+            page.push_str(&format!(
+                "Part {} of {} in {}\n",
+                part,
+                series.post_ids.len(),
+                series.title
+            ));
+        }
+        page.push('\n');
+        page.push_str(&markdown_to_gemtext(p.content));
+        page.push_str(&format!(
+            "\n=> {SITE_URL}/posts/{index}/{} View on the web\n=> /posts All posts\n",
+            p.id
+        ));
+        page
+    }
+
+    fn projects() -> String {
+        let mut page = String::from("# Projects\n");
+        for category in PROJECT_CATEGORIES.iter() {
+            page.push_str(&format!("\n## {}\n", category.title));
+            for project in category.current_projects() {
+                page.push_str(&format!(
+                    "\n### {}\n{}\nTech stack: {}\n",
+                    project.title,
+                    project.description,
+                    project.tech_stack.join(", ")
+                ));
+                if let Some(github_url) = &project.github_url {
+                    page.push_str(&format!("=> {github_url} View Code\n"));
+                }
+                if let Some(try_it_url) = &project.try_it_url {
+                    page.push_str(&format!("=> {try_it_url} Try It\n"));
+                }
+            }
+        }
+        page.push_str("\n=> / Home\n");
+        page
+    }
+
+    /// Convert markdown to gemtext. Gemtext has no inline markup, so links and
+    /// images are collected and listed as link lines after the block they appear
+    /// in, and lists flatten to a single level.
+    fn markdown_to_gemtext(markdown: &str) -> String {
+        let mut out = String::new();
+        // link lines waiting for the end of the current block
+        let mut links: Vec<(String, String)> = vec![];
+        // destination and start in `out` of each open link or image, innermost
+        // last, as an image may sit inside a link
+        let mut open_links: Vec<(String, usize)> = vec![];
+        // start in `out` of the current top-level paragraph
+        let mut paragraph_start = 0;
+        let mut quote_depth = 0;
+        // one entry per nested list; `Some(n)` is the next number of an ordered list
+        let mut lists: Vec<Option<u64>> = vec![];
+        let mut in_code_block = false;
+
+        let flush_links = |out: &mut String, links: &mut Vec<(String, String)>| {
+            for (url, text) in links.drain(..) {
+                out.push_str(&format!("=> {url} {text}\n"));
+            }
+        };
+
+        for event in Parser::new_ext(markdown, Options::all()) {
+            match event {
+                Event::Start(Tag::Heading { level, .. }) => {
+                    out.push_str(match level {
+                        HeadingLevel::H1 => "# ",
+                        HeadingLevel::H2 => "## ",
+                        _ => "### ",
+                    });
+                }
+                Event::End(TagEnd::Heading(_)) => out.push_str("\n\n"),
+                Event::Start(Tag::Paragraph) if lists.is_empty() => {
+                    paragraph_start = out.len();
+                    if quote_depth > 0 {
+                        out.push_str("> ");
+                    }
+                }
+                Event::End(TagEnd::Paragraph) if lists.is_empty() => {
+                    // a paragraph of only an image becomes just its link line
+                    if out[paragraph_start..].trim_start_matches("> ").is_empty() {
+                        out.truncate(paragraph_start);
+                    } else {
+                        out.push('\n');
+                    }
+                    flush_links(&mut out, &mut links);
+                    out.push('\n');
+                }
+                Event::Start(Tag::BlockQuote(_)) => quote_depth += 1,
+                Event::End(TagEnd::BlockQuote(_)) => quote_depth -= 1,
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    in_code_block = true;
+                    out.push_str("```");
+                    if let pulldown_cmark::CodeBlockKind::Fenced(lang) = kind {
+                        out.push_str(&lang);
+                    }
+                    out.push('\n');
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    in_code_block = false;
+                    out.push_str("```\n\n");
+                }
+                Event::Start(Tag::List(first)) => lists.push(first),
+                Event::End(TagEnd::List(_)) => {
+                    lists.pop();
+                    if lists.is_empty() {
+                        flush_links(&mut out, &mut links);
+                        out.push('\n');
+                    }
+                }
+                Event::Start(Tag::Item) => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push('\n');
+                    }
+                    out.push_str("* ");
+                    if let Some(Some(n)) = lists.last_mut() {
+                        out.push_str(&format!("{n}. "));
+                        *n += 1;
+                    }
+                }
+                Event::End(TagEnd::Item) if !out.ends_with('\n') => out.push('\n'),
+                Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                    open_links.push((gemini_url(&dest_url), out.len()));
+                }
+                Event::End(TagEnd::Link) => {
+                    if let Some((url, start)) = open_links.pop() {
+                        links.push((url, out[start..].to_string()));
+                    }
+                }
+                // images can't be shown inline, so their alt text only labels the
+                // link, unless it's also (part of) the text of an enclosing link
+                Event::End(TagEnd::Image) => {
+                    if let Some((url, start)) = open_links.pop() {
+                        links.push((url, out[start..].to_string()));
+                        if open_links.is_empty() {
+                            out.truncate(start);
+                        }
+                    }
+                }
+                Event::Start(Tag::FootnoteDefinition(label)) => {
+                    out.push_str(&format!("[{label}]: "))
+                }
+                Event::FootnoteReference(label) => out.push_str(&format!("[{label}]")),
+                Event::TaskListMarker(checked) => {
+                    out.push_str(if checked { "[x] " } else { "[ ] " })
+                }
+                Event::End(TagEnd::TableCell) => out.push('\t'),
+                Event::End(TagEnd::TableHead | TagEnd::TableRow) => out.push('\n'),
+                Event::End(TagEnd::Table) => out.push('\n'),
+                Event::Text(text) if in_code_block => out.push_str(&text),
+                Event::Text(text)
+                | Event::Code(text)
+                | Event::InlineMath(text)
+                | Event::DisplayMath(text) => out.push_str(&text),
+                // gemtext lines are wrapped by the client
+                Event::SoftBreak => out.push(' '),
+                Event::HardBreak => {
+                    out.push('\n');
+                    out.push_str(&"> ".repeat(quote_depth.min(1)));
+                }
+                Event::Rule => out.push_str("---\n\n"),
+                _ => {}
+            }
+        }
+
+        flush_links(&mut out, &mut links);
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    /// posts are mirrored over gemini, so links to them stay relative; anything
+    /// else on the site is only available over https
+    fn gemini_url(url: &str) -> String {
+        if url.starts_with("/posts") {
+            url.to_string()
+        } else {
+            absolute_url(url)
+        }
+    }
+}
+
+// gopher
+
+/// A Gopher mirror of the posts and projects, served when `GOPHER_ADDR` (e.g.
+/// `0.0.0.0:70`) is set. Menus link back to `GOPHER_HOSTNAME` and
+/// `GOPHER_PORT`, which default to what clients can reach, not the bind address.
+mod gopher {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinSet;
+
+    use super::{HOMEPAGE, MIRRORED_PATHS, POSTS, PROJECT_CATEGORIES, absolute_url};
+
+    const MAX_SELECTOR_LENGTH: u64 = 1024;
+    /// longest a connection may take, from accepting it to closing it
+    const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+    /// width of informational text in menus, leaving room for the client's item markers
+    const MENU_WIDTH: usize = 67;
+
+    #[derive(Clone)]
+    pub struct Config {
+        addr: String,
+        hostname: String,
+        port: u16,
+    }
+
+    impl Config {
+        /// `None` if the gopher server is not enabled
+        pub fn from_env() -> Option<Config> {
+            Some(Config {
+                addr: std::env::var("GOPHER_ADDR").ok()?,
+                hostname: std::env::var("GOPHER_HOSTNAME")
+                    .unwrap_or_else(|_| "ryangeary.dev".to_string()),
+                port: std::env::var("GOPHER_PORT")
+                    .ok()
+                    .and_then(|port| port.parse().ok())
+                    .unwrap_or(70),
+            })
+        }
+    }
+
+    /// serves until `shutdown` resolves, then waits for in-flight requests
+    pub async fn serve(config: Config, shutdown: impl Future<Output = ()>) -> std::io::Result<()> {
+        let listener = TcpListener::bind(&config.addr).await?;
+        tracing::info!("gopher server listening on {}", config.addr);
+
+        let mut connections = JoinSet::new();
+        let mut shutdown = std::pin::pin!(shutdown);
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                () = &mut shutdown => break,
+            };
+            let (stream, peer) = match accepted {
+                Ok(accepted) => accepted,
+                // e.g. out of file descriptors, which closing connections will fix
+                Err(e) => {
+                    tracing::error!("gopher accept error: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            let config = config.clone();
+            connections.spawn(async move {
+                let handled =
+                    tokio::time::timeout(CONNECTION_TIMEOUT, handle_connection(&config, stream));
+                match handled.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => tracing::debug!("gopher connection from {} failed: {}", peer, e),
+                    Err(_) => tracing::debug!("gopher connection from {} timed out", peer),
+                }
+            });
+            while connections.try_join_next().is_some() {}
+        }
+
+        // each connection is bounded by CONNECTION_TIMEOUT
+        connections.join_all().await;
+        Ok(())
+    }
+
+    async fn handle_connection(config: &Config, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut selector = String::new();
+        let mut reader = BufReader::new((&mut stream).take(MAX_SELECTOR_LENGTH));
+        reader.read_line(&mut selector).await?;
+
+        // gopher+ clients may append a tab and extra fields
+        let selector = selector.trim_end().split('\t').next().unwrap_or_default();
+        tracing::info!("gopher {:?}", selector);
+
+        stream
+            .write_all(respond(config, selector).as_bytes())
+            .await?;
+        stream.shutdown().await
+    }
+
+    fn respond(config: &Config, selector: &str) -> String {
+        let segments = selector
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+
+        match segments.as_slice() {
+            [] => index(config),
+            ["posts"] => posts(config),
+            ["posts", index, file] => {
+                let id = file.strip_suffix(".txt").unwrap_or(file);
+                match index.parse::<usize>().ok().and_then(|i| POSTS.get(i)) {
+                    Some(post) if post.id == id => text(&post.plaintext()),
+                    _ => error(config, "Not found"),
+                }
+            }
+            ["projects"] => projects(config),
+            _ => error(config, "Not found"),
+        }
+    }
+
+    /// a menu line of the given item type
+    fn item(config: &Config, item_type: char, display: &str, selector: &str) -> String {
+        format!(
+            "{item_type}{display}\t{selector}\t{}\t{}\r\n",
+            config.hostname, config.port
+        )
+    }
+
+    /// a non-selectable line of text in a menu
+    fn info(text: &str) -> String {
+        format!("i{text}\t\terror.host\t1\r\n")
+    }
+
+    /// a link to a non-gopher url, per the `URL:` selector convention
+    fn url(config: &Config, display: &str, url: &str) -> String {
+        item(config, 'h', display, &format!("URL:{}", absolute_url(url)))
+    }
+
+    fn error(config: &Config, message: &str) -> String {
+        item(config, '3', message, "") + ".\r\n"
+    }
+
+    fn index(config: &Config) -> String {
+        let mut menu = info(HOMEPAGE.name) + &info("");
+        for role in HOMEPAGE.tagline() {
+            menu.push_str(&info(&role));
+        }
+        menu.push_str(&info(""));
+        for b in &HOMEPAGE.buttons {
+            if MIRRORED_PATHS.contains(&b.href) {
+                menu.push_str(&item(config, '1', b.title, b.href));
+            } else {
+                menu.push_str(&url(config, b.title, b.href));
+            }
+        }
+        menu + ".\r\n"
+    }
+
+    fn posts(config: &Config) -> String {
+        let mut menu = info("Posts") + &info("");
+        for (index, p) in POSTS.iter().enumerate().rev() {
+            menu.push_str(&item(
+                config,
+                '0',
+                &format!("{} {}", p.formatted_date(), p.title),
+                &format!("/posts/{}/{}.txt", index, p.id),
+            ));
+        }
+        menu + ".\r\n"
+    }
+
+    fn projects(config: &Config) -> String {
+        let mut menu = info("Projects");
+        for category in PROJECT_CATEGORIES.iter() {
+            menu.push_str(&info(""));
+            menu.push_str(&info(category.title));
+            for project in category.current_projects() {
+                menu.push_str(&info(""));
+                menu.push_str(&info(&format!("## {}", project.title)));
+                for line in wrap(project.description, MENU_WIDTH) {
+                    menu.push_str(&info(&line));
+                }
+                for line in wrap(
+                    &format!("Tech stack: {}", project.tech_stack.join(", ")),
+                    MENU_WIDTH,
+                ) {
+                    menu.push_str(&info(&line));
+                }
+                if let Some(github_url) = &project.github_url {
+                    menu.push_str(&url(config, "View Code", github_url));
+                }
+                if let Some(try_it_url) = &project.try_it_url {
+                    menu.push_str(&url(config, "Try It", try_it_url));
+                }
+            }
+        }
+        menu + ".\r\n"
+    }
+
+    /// a text file response, with CRLF line endings and leading dots escaped
+    fn text(body: &str) -> String {
+        let mut out = String::new();
+        for line in body.lines() {
+            if line.starts_with('.') {
+                out.push('.');
+            }
+            out.push_str(line);
+            out.push_str("\r\n");
+        }
+        out + ".\r\n"
+    }
+
+    /// greedily wrap text into lines of at most `width` characters
+    fn wrap(text: &str, width: usize) -> Vec<String> {
+        let mut lines = vec![];
+        let mut line = String::new();
+        for word in text.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
+}
+
+// finger
+
+/// A finger (RFC 1288) responder with the homepage summary, served when
+/// `FINGER_ADDR` (e.g. `0.0.0.0:79`) is set. Every query gets the same answer.
+mod finger {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::task::JoinSet;
+
+    use super::{HOMEPAGE, absolute_url};
+
+    const MAX_QUERY_LENGTH: u64 = 1024;
+    /// longest a connection may take, from accepting it to closing it
+    const CONNECTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// `None` if the finger server is not enabled
+    pub fn addr_from_env() -> Option<String> {
+        std::env::var("FINGER_ADDR").ok()
+    }
+
+    /// serves until `shutdown` resolves, then waits for in-flight requests
+    pub async fn serve(addr: String, shutdown: impl Future<Output = ()>) -> std::io::Result<()> {
+        let listener = TcpListener::bind(&addr).await?;
+        tracing::info!("finger server listening on {}", addr);
+
+        let mut connections = JoinSet::new();
+        let mut shutdown = std::pin::pin!(shutdown);
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                () = &mut shutdown => break,
+            };
+            let (stream, peer) = match accepted {
+                Ok(accepted) => accepted,
+                // e.g. out of file descriptors, which closing connections will fix
+                Err(e) => {
+                    tracing::error!("finger accept error: {}", e);
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            connections.spawn(async move {
+                let handled = tokio::time::timeout(CONNECTION_TIMEOUT, handle_connection(stream));
+                match handled.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => tracing::debug!("finger connection from {} failed: {}", peer, e),
+                    Err(_) => tracing::debug!("finger connection from {} timed out", peer),
+                }
+            });
+            while connections.try_join_next().is_some() {}
+        }
+
+        // each connection is bounded by CONNECTION_TIMEOUT
+        connections.join_all().await;
+        Ok(())
+    }
+
+    async fn handle_connection(mut stream: TcpStream) -> std::io::Result<()> {
+        let mut query = String::new();
+        let mut reader = BufReader::new((&mut stream).take(MAX_QUERY_LENGTH));
+        reader.read_line(&mut query).await?;
+        tracing::info!("finger {:?}", query.trim_end());
+
+        stream.write_all(summary().as_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    fn summary() -> String {
+        let mut out = format!("{}\r\n", HOMEPAGE.name);
+        for role in HOMEPAGE.tagline() {
+            out.push_str(&format!("{role}\r\n"));
+        }
+        out.push_str("\r\n");
+        for b in &HOMEPAGE.buttons {
+            out.push_str(&format!("{}: {}\r\n", b.title, absolute_url(b.href)));
+        }
+        out
+    }
+}
+
+// pdf
+
+/// PDF exports of posts and the résumé, laid out from the same markdown and
+/// `Resume` as their html pages. Only the PDF built-in fonts are used, so there
+/// are no font files to embed, but characters outside Windows-1252 (e.g.
+/// emoji) are dropped.
+mod pdf {
+    use printpdf::image_crate::{self, DynamicImage, GenericImageView};
+    use printpdf::{
+        BuiltinFont, Color, Image, ImageTransform, IndirectFontRef, Mm, PdfDocument,
+        PdfDocumentReference, PdfLayerReference, Rect, Rgb,
+    };
+    use pulldown_cmark::{Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+
+    use super::{Assets, PROJECTS, Post, Resume, SITE_URL, absolute_url, formatted_date_range};
+
+    /// A4, in mm
+    const PAGE_WIDTH: f32 = 210.0;
+    const PAGE_HEIGHT: f32 = 297.0;
+    const MARGIN: f32 = 20.0;
+    const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+    /// mm per point
+    const PT: f32 = 0.3528;
+    /// font sizes, in points
+    const BODY_SIZE: f32 = 11.0;
+    const SMALL_SIZE: f32 = 9.0;
+    const CODE_SIZE: f32 = 9.0;
+    /// indent of each level of list or block quote, in mm
+    const INDENT: f32 = 6.0;
+    /// resolution images are shown at, unless they are too big for the page
+    const IMAGE_DPI: f32 = 144.0;
+    const MAX_IMAGE_HEIGHT: f32 = 120.0;
+
+    /// Helvetica glyph widths, in 1/1000 em, of ' ' through '~'
+    const HELVETICA_WIDTHS: [u16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722,
+        722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556,
+        556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500,
+        500, 334, 260, 334, 584,
+    ];
+
+    pub fn post(post: &Post, url: &str) -> Result<Vec<u8>, printpdf::Error> {
+        let mut writer = Writer::new(post.title, url);
+        writer.heading(post.title, HeadingLevel::H1);
+        writer.text(
+            &[Span::new(
+                format!(
+                    "{} · {}",
+                    post.formatted_date(),
+                    post.formatted_reading_time()
+                ),
+                Style::Italic,
+            )],
+            SMALL_SIZE,
+            0.0,
+        );
+        writer.gap(4.0);
+        writer.markdown(post.content);
+        writer.finish()
+    }
+
+    pub fn resume(resume: &Resume, url: &str) -> Result<Vec<u8>, printpdf::Error> {
+        let mut writer = Writer::new(&format!("{} - Résumé", resume.name), url);
+        writer.heading(resume.name, HeadingLevel::H1);
+        writer.text(&[Span::new(resume.label, Style::Bold)], 13.0, 0.0);
+        writer.gap(1.0);
+        if let Some(summary) = resume.summary {
+            writer.paragraph(&[Span::new(summary, Style::Regular)], 0.0);
+        }
+        writer.text(&[Span::new(SITE_URL, Style::Regular)], SMALL_SIZE, 0.0);
+        for profile in &resume.profiles {
+            writer.text(
+                &[Span::new(
+                    format!("{}: {}", profile.network, profile.url),
+                    Style::Regular,
+                )],
+                SMALL_SIZE,
+                0.0,
+            );
+        }
+
+        if !resume.work.is_empty() {
+            writer.heading("Experience", HeadingLevel::H2);
+            for work in &resume.work {
+                let mut title = vec![Span::new(work.position, Style::Bold)];
+                if let Some(organization) = work.organization {
+                    title.push(Span::new(format!(", {organization}"), Style::Bold));
+                }
+                writer.text(&title, BODY_SIZE, 0.0);
+                if let Some(dates) = formatted_date_range(work.started, work.ended, "present") {
+                    writer.text(&[Span::new(dates, Style::Italic)], SMALL_SIZE, 0.0);
+                }
+                if let Some(summary) = work.summary {
+                    writer.text(&[Span::new(summary, Style::Regular)], BODY_SIZE, 0.0);
+                }
+                for highlight in &work.highlights {
+                    writer.text(
+                        &[Span::new(format!("• {highlight}"), Style::Regular)],
+                        BODY_SIZE,
+                        INDENT,
+                    );
+                }
+                writer.gap(3.0);
+            }
+        }
+
+        if !resume.education.is_empty() {
+            writer.heading("Education", HeadingLevel::H2);
+            for education in &resume.education {
+                writer.text(
+                    &[Span::new(education.institution, Style::Bold)],
+                    BODY_SIZE,
+                    0.0,
+                );
+                writer.text(
+                    &[Span::new(
+                        format!("{}, {}", education.study_type, education.area),
+                        Style::Regular,
+                    )],
+                    BODY_SIZE,
+                    0.0,
+                );
+                if let Some(dates) =
+                    formatted_date_range(education.started, education.ended, "present")
+                {
+                    writer.text(&[Span::new(dates, Style::Italic)], SMALL_SIZE, 0.0);
+                }
+                writer.gap(3.0);
+            }
+        }
+
+        if !resume.skills.is_empty() {
+            writer.heading("Skills", HeadingLevel::H2);
+            for group in &resume.skills {
+                writer.text(
+                    &[
+                        Span::new(format!("{}: ", group.name), Style::Bold),
+                        Span::new(group.skills.join(", "), Style::Regular),
+                    ],
+                    BODY_SIZE,
+                    0.0,
+                );
+            }
+        }
+
+        writer.heading("Projects", HeadingLevel::H2);
+        for project in PROJECTS.iter() {
+            writer.text(
+                &[
+                    Span::new(project.title, Style::Bold),
+                    Span::new(format!(" – {}", project.description), Style::Regular),
+                ],
+                BODY_SIZE,
+                0.0,
+            );
+            writer.text(
+                &[Span::new(absolute_url(&project.url()), Style::Regular)],
+                SMALL_SIZE,
+                0.0,
+            );
+            writer.gap(1.5);
+        }
+
+        writer.finish()
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Style {
+        Regular,
+        Bold,
+        Italic,
+        Code,
+    }
+
+    /// a run of text in one style
+    struct Span {
+        text: String,
+        style: Style,
+    }
+
+    impl Span {
+        fn new(text: impl Into<String>, style: Style) -> Span {
+            Span {
+                text: text.into(),
+                style,
+            }
+        }
+    }
+
+    /// width of `text` in mm
+    fn text_width(text: &str, style: Style, size: f32) -> f32 {
+        let units: u32 = text
+            .chars()
+            .map(|c| match style {
+                Style::Code => 600,
+                _ => u32::from(
+                    HELVETICA_WIDTHS
+                        .get((c as usize).wrapping_sub(' ' as usize))
+                        .copied()
+                        .unwrap_or(556),
+                ),
+            })
+            .sum();
+        // bold glyphs are a little wider; close enough for wrapping
+        let units = if style == Style::Bold {
+            units as f32 * 1.06
+        } else {
+            units as f32
+        };
+        units / 1000.0 * size * PT
+    }
+
+    /// the words of `spans`, each as its styled pieces (e.g. a quote mark and
+    /// the text after it), with whether it is preceded by a space
+    fn words(spans: &[Span]) -> Vec<(Vec<(&str, Style)>, bool)> {
+        let mut words: Vec<(Vec<(&str, Style)>, bool)> = vec![];
+        let mut space = false;
+        for span in spans {
+            for (i, piece) in span.text.split(char::is_whitespace).enumerate() {
+                space |= i > 0;
+                if piece.is_empty() {
+                    continue;
+                }
+                match words.last_mut() {
+                    Some((pieces, _)) if !space => pieces.push((piece, span.style)),
+                    _ => words.push((vec![(piece, span.style)], space && !words.is_empty())),
+                }
+                space = false;
+            }
+        }
+        words
+    }
+
+    /// append `text` to `line`, continuing its last run if the style matches
+    fn push_run(line: &mut Vec<(String, Style)>, text: &str, style: Style) {
+        match line.last_mut() {
+            Some((run, last)) if *last == style => run.push_str(text),
+            _ => line.push((text.to_string(), style)),
+        }
+    }
+
+    /// Lays out text top to bottom, starting new pages as needed.
+    struct Writer {
+        doc: PdfDocumentReference,
+        layer: PdfLayerReference,
+        fonts: [IndirectFontRef; 4],
+        /// distance of the next line from the bottom of the page, in mm
+        y: f32,
+        /// printed at the bottom of every page, alongside the page number
+        footer: String,
+        pages: usize,
+    }
+
+    impl Writer {
+        fn new(title: &str, url: &str) -> Writer {
+            let (doc, page, layer) =
+                PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "content");
+            let font = |font| {
+                doc.add_builtin_font(font)
+                    .expect("built-in fonts are always available")
+            };
+            let fonts = [
+                font(BuiltinFont::Helvetica),
+                font(BuiltinFont::HelveticaBold),
+                font(BuiltinFont::HelveticaOblique),
+                font(BuiltinFont::Courier),
+            ];
+            let layer = doc.get_page(page).get_layer(layer);
+            let writer = Writer {
+                doc,
+                layer,
+                fonts,
+                y: PAGE_HEIGHT - MARGIN,
+                footer: url.to_string(),
+                pages: 1,
+            };
+            writer.draw_footer();
+            writer
+        }
+
+        fn finish(self) -> Result<Vec<u8>, printpdf::Error> {
+            self.doc.save_to_bytes()
+        }
+
+        fn font(&self, style: Style) -> &IndirectFontRef {
+            &self.fonts[style as usize]
+        }
+
+        fn new_page(&mut self) {
+            let (page, layer) = self
+                .doc
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "content");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+            self.pages += 1;
+            self.draw_footer();
+        }
+
+        fn draw_footer(&self) {
+            self.layer.set_fill_color(gray(0.4));
+            let footer = format!("{} · {}", self.footer, self.pages);
+            self.layer.use_text(
+                footer,
+                SMALL_SIZE,
+                Mm(MARGIN),
+                Mm(MARGIN / 2.0),
+                self.font(Style::Regular),
+            );
+            self.layer.set_fill_color(gray(0.0));
+        }
+
+        /// start a new page unless `height` mm fits on this one
+        fn ensure(&mut self, height: f32) {
+            if self.y - height < MARGIN {
+                self.new_page();
+            }
+        }
+
+        fn gap(&mut self, height: f32) {
+            self.y -= height;
+        }
+
+        fn heading(&mut self, text: &str, level: HeadingLevel) {
+            let size = match level {
+                HeadingLevel::H1 => 20.0,
+                HeadingLevel::H2 => 15.0,
+                HeadingLevel::H3 => 13.0,
+                _ => BODY_SIZE,
+            };
+            self.gap(if level == HeadingLevel::H1 { 0.0 } else { 4.0 });
+            // keep headings with at least a line of what follows
+            self.ensure(size * PT * 1.4 + BODY_SIZE * PT * 1.4);
+            self.layer.set_fill_color(purple());
+            self.text(&[Span::new(text, Style::Bold)], size, 0.0);
+            self.layer.set_fill_color(gray(0.0));
+            self.gap(2.0);
+        }
+
+        fn paragraph(&mut self, spans: &[Span], indent: f32) {
+            self.text(spans, BODY_SIZE, indent);
+            self.gap(3.0);
+        }
+
+        /// word-wrapped `spans`, `indent` mm from the left margin
+        fn text(&mut self, spans: &[Span], size: f32, indent: f32) {
+            let max_width = CONTENT_WIDTH - indent;
+            let mut line: Vec<(String, Style)> = vec![];
+            let mut width = 0.0;
+
+            for (word, space) in words(spans) {
+                let word_width: f32 = word
+                    .iter()
+                    .map(|(piece, style)| text_width(piece, *style, size))
+                    .sum();
+                // a space is in the style of the text before it
+                let space = match line.last() {
+                    Some((_, style)) if space => Some(*style),
+                    _ => None,
+                };
+                let space_width = space.map_or(0.0, |style| text_width(" ", style, size));
+                if !line.is_empty() && width + space_width + word_width > max_width {
+                    self.line(&line, size, indent);
+                    line.clear();
+                    width = 0.0;
+                } else if let Some(style) = space {
+                    push_run(&mut line, " ", style);
+                    width += space_width;
+                }
+
+                for (piece, style) in word {
+                    // words wider than a whole line, e.g. long urls, are split anywhere
+                    for part in split_to_width(piece, style, size, max_width) {
+                        let part_width = text_width(&part, style, size);
+                        if !line.is_empty() && width + part_width > max_width {
+                            self.line(&line, size, indent);
+                            line.clear();
+                            width = 0.0;
+                        }
+                        width += part_width;
+                        push_run(&mut line, &part, style);
+                    }
+                }
+            }
+            if !line.is_empty() {
+                self.line(&line, size, indent);
+            }
+        }
+
+        fn line(&mut self, line: &[(String, Style)], size: f32, indent: f32) {
+            let height = size * PT * 1.4;
+            self.ensure(height);
+            self.y -= height;
+            let mut x = MARGIN + indent;
+            for (text, style) in line {
+                self.layer
+                    .use_text(text, size, Mm(x), Mm(self.y), self.font(*style));
+                x += text_width(text, *style, size);
+            }
+        }
+
+        /// preformatted lines on a gray background, wrapped at the right margin
+        fn code_block(&mut self, code: &str, indent: f32) {
+            let height = CODE_SIZE * PT * 1.4;
+            let columns =
+                ((CONTENT_WIDTH - indent - 4.0) / text_width(" ", Style::Code, CODE_SIZE)) as usize;
+            for line in code.trim_end_matches('\n').lines() {
+                let chars = line.chars().collect::<Vec<_>>();
+                let mut chunks = chars
+                    .chunks(columns.max(1))
+                    .map(|chunk| chunk.iter().collect::<String>())
+                    .collect::<Vec<_>>();
+                // keep blank lines
+                if chunks.is_empty() {
+                    chunks.push(String::new());
+                }
+                for chunk in chunks {
+                    self.ensure(height);
+                    self.y -= height;
+                    self.layer.set_fill_color(gray(0.93));
+                    self.layer.add_rect(Rect::new(
+                        Mm(MARGIN + indent),
+                        Mm(self.y - height * 0.3),
+                        Mm(PAGE_WIDTH - MARGIN),
+                        Mm(self.y + height * 0.7),
+                    ));
+                    self.layer.set_fill_color(gray(0.0));
+                    self.layer.use_text(
+                        chunk,
+                        CODE_SIZE,
+                        Mm(MARGIN + indent + 2.0),
+                        Mm(self.y),
+                        self.font(Style::Code),
+                    );
+                }
+            }
+            self.gap(3.0);
+        }
+
+        /// an image from `Assets`, scaled to fit, with its alt text as a caption;
+        /// false if `src` isn't a decodable asset
+        fn image(&mut self, src: &str, alt: &str, indent: f32) -> bool {
+            let Some(image) = src
+                .strip_prefix("/static/")
+                .and_then(Assets::get)
+                .and_then(|file| image_crate::load_from_memory(&file.data).ok())
+            else {
+                return false;
+            };
+            // printpdf only understands 8-bit color
+            let image = DynamicImage::ImageRgba8(image.to_rgba8());
+
+            let (width, height) = image.dimensions();
+            let natural_width = width as f32 / IMAGE_DPI * 25.4;
+            let natural_height = height as f32 / IMAGE_DPI * 25.4;
+            let scale = ((CONTENT_WIDTH - indent) / natural_width)
+                .min(MAX_IMAGE_HEIGHT / natural_height)
+                .min(1.0);
+
+            self.ensure(natural_height * scale + SMALL_SIZE * PT * 1.4);
+            self.y -= natural_height * scale;
+            Image::from_dynamic_image(&image).add_to_layer(
+                self.layer.clone(),
+                ImageTransform {
+                    translate_x: Some(Mm(MARGIN + indent)),
+                    translate_y: Some(Mm(self.y)),
+                    scale_x: Some(scale),
+                    scale_y: Some(scale),
+                    dpi: Some(IMAGE_DPI),
+                    ..Default::default()
+                },
+            );
+            if !alt.is_empty() {
+                self.text(&[Span::new(alt, Style::Italic)], SMALL_SIZE, indent);
+            }
+            self.gap(3.0);
+            true
+        }
+
+        fn rule(&mut self) {
+            self.ensure(4.0);
+            self.gap(2.0);
+            self.layer.set_fill_color(gray(0.7));
+            self.layer.add_rect(Rect::new(
+                Mm(MARGIN),
+                Mm(self.y),
+                Mm(PAGE_WIDTH - MARGIN),
+                Mm(self.y + 0.3),
+            ));
+            self.layer.set_fill_color(gray(0.0));
+            self.gap(2.0);
+        }
+
+        /// Lays out markdown block by block, like `markdown_to_plaintext`: links
+        /// are followed by their url, and footnotes are left where they are
+        /// defined, in a smaller font.
+        fn markdown(&mut self, markdown: &str) {
+            let mut spans: Vec<Span> = vec![];
+            let mut strong = 0;
+            let mut emphasis = 0;
+            // one entry per nested list; `Some(n)` is the next number of an ordered list
+            let mut lists: Vec<Option<u64>> = vec![];
+            // destination and first span of each open link
+            let mut links: Vec<(String, usize)> = vec![];
+            let mut quote_depth = 0;
+            let mut in_footnote = false;
+            let mut code_block: Option<String> = None;
+            // destination and alt text of the open image
+            let mut image: Option<(String, String)> = None;
+
+            let indent = |lists: &Vec<Option<u64>>, quote_depth: usize| {
+                (lists.len() + quote_depth) as f32 * INDENT
+            };
+            let flush =
+                |writer: &mut Writer, spans: &mut Vec<Span>, indent: f32, footnote: bool| {
+                    if !spans.is_empty() {
+                        let size = if footnote { SMALL_SIZE } else { BODY_SIZE };
+                        writer.text(spans, size, indent);
+                        spans.clear();
+                    }
+                };
+
+            for event in Parser::new_ext(markdown, Options::all()) {
+                let style = if strong > 0 {
+                    Style::Bold
+                } else if emphasis > 0 || quote_depth > 0 {
+                    Style::Italic
+                } else {
+                    Style::Regular
+                };
+                match event {
+                    Event::End(TagEnd::Paragraph) => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                        self.gap(if lists.is_empty() { 3.0 } else { 1.0 });
+                    }
+                    Event::Start(Tag::Heading { .. }) => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                    }
+                    Event::End(TagEnd::Heading(level)) => {
+                        let text = spans.drain(..).map(|s| s.text).collect::<String>();
+                        self.heading(&text, level);
+                    }
+                    Event::Start(Tag::BlockQuote(_)) => quote_depth += 1,
+                    Event::End(TagEnd::BlockQuote(_)) => quote_depth -= 1,
+                    Event::Start(Tag::CodeBlock(_)) => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                        code_block = Some(String::new());
+                    }
+                    Event::End(TagEnd::CodeBlock) => {
+                        if let Some(code) = code_block.take() {
+                            self.code_block(&code, indent(&lists, quote_depth));
+                        }
+                    }
+                    Event::Start(Tag::List(first)) => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                        lists.push(first);
+                    }
+                    Event::End(TagEnd::List(_)) => {
+                        lists.pop();
+                        if lists.is_empty() {
+                            self.gap(2.0);
+                        }
+                    }
+                    Event::Start(Tag::Item) => {
+                        let marker = match lists.last_mut() {
+                            Some(Some(n)) => {
+                                *n += 1;
+                                format!("{}. ", *n - 1)
+                            }
+                            _ => "• ".to_string(),
+                        };
+                        spans.push(Span::new(marker, Style::Regular));
+                    }
+                    Event::End(TagEnd::Item) => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                    }
+                    Event::Start(Tag::Strong) => strong += 1,
+                    Event::End(TagEnd::Strong) => strong -= 1,
+                    Event::Start(Tag::Emphasis) => emphasis += 1,
+                    Event::End(TagEnd::Emphasis) => emphasis -= 1,
+                    Event::Start(Tag::Link { dest_url, .. }) => {
+                        links.push((absolute_url(&dest_url), spans.len()));
+                    }
+                    Event::End(TagEnd::Link) => {
+                        if let Some((dest_url, start)) = links.pop() {
+                            let text = spans
+                                .iter()
+                                .skip(start)
+                                .map(|s| &*s.text)
+                                .collect::<String>();
+                            if text != dest_url {
+                                spans.push(Span::new(format!(" ({dest_url})"), Style::Regular));
+                            }
+                        }
+                    }
+                    Event::Start(Tag::Image { dest_url, .. }) => {
+                        image = Some((dest_url.to_string(), String::new()));
+                    }
+                    Event::End(TagEnd::Image) => {
+                        if let Some((src, alt)) = image.take() {
+                            flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                            if !self.image(&src, &alt, indent(&lists, quote_depth)) {
+                                spans.push(Span::new(
+                                    format!("[image: {alt}] ({})", absolute_url(&src)),
+                                    Style::Italic,
+                                ));
+                            }
+                        }
+                    }
+                    Event::Start(Tag::FootnoteDefinition(label)) => {
+                        in_footnote = true;
+                        spans.push(Span::new(format!("[{label}] "), Style::Bold));
+                    }
+                    Event::End(TagEnd::FootnoteDefinition) => in_footnote = false,
+                    Event::FootnoteReference(label) => {
+                        spans.push(Span::new(format!("[{label}]"), Style::Regular));
+                    }
+                    Event::Start(Tag::TableHead) => strong += 1,
+                    Event::End(TagEnd::TableHead) => {
+                        strong -= 1;
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                    }
+                    Event::End(TagEnd::TableCell) => spans.push(Span::new("   ", style)),
+                    Event::End(TagEnd::TableRow) => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                    }
+                    Event::End(TagEnd::Table) => self.gap(3.0),
+                    Event::TaskListMarker(checked) => {
+                        spans.push(Span::new(if checked { "[x] " } else { "[ ] " }, style));
+                    }
+                    Event::Text(text) => match (&mut code_block, &mut image) {
+                        (Some(code), _) => code.push_str(&text),
+                        (_, Some((_, alt))) => alt.push_str(&text),
+                        _ => spans.push(Span::new(text.to_string(), style)),
+                    },
+                    Event::Code(text) => spans.push(Span::new(text.to_string(), Style::Code)),
+                    Event::InlineMath(text) | Event::DisplayMath(text) => {
+                        spans.push(Span::new(text.to_string(), style));
+                    }
+                    Event::SoftBreak => spans.push(Span::new(" ", style)),
+                    Event::HardBreak => {
+                        flush(self, &mut spans, indent(&lists, quote_depth), in_footnote);
+                    }
+                    Event::Rule => self.rule(),
+                    _ => {}
+                }
+            }
+            flush(self, &mut spans, 0.0, false);
+        }
+    }
+
+    /// `word` split into pieces no wider than `max_width`
+    fn split_to_width(word: &str, style: Style, size: f32, max_width: f32) -> Vec<String> {
+        let mut pieces = vec![String::new()];
+        for c in word.chars() {
+            let piece = pieces.last_mut().unwrap();
+            piece.push(c);
+            if text_width(piece, style, size) > max_width && piece.chars().count() > 1 {
+                piece.pop();
+                pieces.push(c.to_string());
+            }
+        }
+        pieces
+    }
+
+    fn gray(level: f32) -> Color {
+        Color::Rgb(Rgb::new(level, level, level, None))
+    }
+
+    /// the purple of headings on the site
+    fn purple() -> Color {
+        Color::Rgb(Rgb::new(0.3, 0.11, 0.53, None))
+    }
+}
+
+// comments
+
+/// Reader comments on posts, stored in SQLite at `COMMENTS_DB` (e.g.
+/// `/data/comments.sqlite`) and only enabled when it is set. New comments wait
+/// in a moderation queue, worked through with the `comments` subcommand, and
+/// aren't shown until they are approved.
+mod comments {
+    use std::collections::HashSet;
+    use std::net::IpAddr;
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Duration;
+
+    use chrono::{DateTime, Utc};
+    use pulldown_cmark::{Event, Options, Parser};
+    use rusqlite::{Connection, OptionalExtension, params};
+    use strum::IntoEnumIterator;
+
+    use super::RecentActivity;
+
+    /// longest author name and comment, in characters
+    pub const MAX_AUTHOR_LENGTH: usize = 80;
+    pub const MAX_BODY_LENGTH: usize = 5000;
+
+    const SCHEMA: &str = "
+        PRAGMA journal_mode = WAL;
+        CREATE TABLE IF NOT EXISTS comments (
+            id INTEGER PRIMARY KEY,
+            post_id TEXT NOT NULL,
+            parent_id INTEGER REFERENCES comments (id),
+            author TEXT NOT NULL,
+            body TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending'
+        );
+        CREATE INDEX IF NOT EXISTS comments_by_post ON comments (post_id, status);
+    ";
+
+    static STORE: OnceLock<Store> = OnceLock::new();
+
+    pub struct Store {
+        db: Mutex<Connection>,
+        /// comments accepted from each address, for rate limiting
+        recent: RecentActivity,
+    }
+
+    pub struct Comment {
+        pub id: i64,
+        pub post_id: String,
+        pub parent_id: Option<i64>,
+        pub author: String,
+        /// markdown-lite, see `render`
+        pub body: String,
+        pub created_at: DateTime<Utc>,
+    }
+
+    /// where a comment is in moderation
+    #[derive(strum::Display, strum::EnumIter, PartialEq, Eq, Copy, Clone)]
+    #[strum(serialize_all = "snake_case")]
+    pub enum Status {
+        Pending,
+        Approved,
+        Rejected,
+    }
+
+    pub enum SubmitError {
+        RateLimited,
+        /// shown to the commenter
+        Invalid(&'static str),
+        Database(rusqlite::Error),
+    }
+
+    impl From<rusqlite::Error> for SubmitError {
+        fn from(e: rusqlite::Error) -> Self {
+            SubmitError::Database(e)
+        }
+    }
+
+    /// `None` if comments are not enabled
+    pub fn path_from_env() -> Option<PathBuf> {
+        std::env::var_os("COMMENTS_DB").map(PathBuf::from)
+    }
+
+    /// open (creating if needed) the database at `path`, and enable comments
+    pub fn init(path: &Path) -> rusqlite::Result<()> {
+        let store = Store {
+            db: Mutex::new(open(path)?),
+            recent: RecentActivity::new(5, Duration::from_secs(60 * 60)),
+        };
+        // already enabled is fine too
+        let _ = STORE.set(store);
+        Ok(())
+    }
+
+    /// the comment store, if comments are enabled
+    pub fn store() -> Option<&'static Store> {
+        STORE.get()
+    }
+
+    fn open(path: &Path) -> rusqlite::Result<Connection> {
+        let db = Connection::open(path)?;
+        db.execute_batch(SCHEMA)?;
+        Ok(db)
+    }
+
+    fn comment(row: &rusqlite::Row) -> rusqlite::Result<Comment> {
+        Ok(Comment {
+            id: row.get("id")?,
+            post_id: row.get("post_id")?,
+            parent_id: row.get("parent_id")?,
+            author: row.get("author")?,
+            body: row.get("body")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    fn with_status(
+        db: &Connection,
+        status: Status,
+        post_id: Option<&str>,
+    ) -> rusqlite::Result<Vec<Comment>> {
+        db.prepare(
+            "SELECT * FROM comments WHERE status = ?1 AND (?2 IS NULL OR post_id = ?2) ORDER BY id",
+        )?
+        .query_map(params![status.to_string(), post_id], comment)?
+        .collect()
+    }
+
+    fn set_status(db: &Connection, id: i64, status: Status) -> rusqlite::Result<bool> {
+        let changed = db.execute(
+            "UPDATE comments SET status = ?1 WHERE id = ?2",
+            params![status.to_string(), id],
+        )?;
+        Ok(changed > 0)
+    }
+
+    impl Store {
+        /// check that the database responds
+        pub fn ping(&self) -> rusqlite::Result<()> {
+            self.db
+                .lock()
+                .unwrap()
+                .query_row("SELECT 1", [], |_| Ok(()))
+        }
+
+        /// approved comments on a post, oldest first
+        pub fn approved(&self, post_id: &str) -> rusqlite::Result<Vec<Comment>> {
+            with_status(&self.db.lock().unwrap(), Status::Approved, Some(post_id))
+        }
+
+        /// comments awaiting moderation, oldest first
+        pub fn pending(&self) -> rusqlite::Result<Vec<Comment>> {
+            with_status(&self.db.lock().unwrap(), Status::Pending, None)
+        }
+
+        /// how many comments have each status
+        pub fn counts(&self) -> rusqlite::Result<Vec<(Status, usize)>> {
+            let db = self.db.lock().unwrap();
+            Status::iter()
+                .map(|status| {
+                    let count = db.query_row(
+                        "SELECT COUNT(*) FROM comments WHERE status = ?1",
+                        [status.to_string()],
+                        |row| row.get(0),
+                    )?;
+                    Ok((status, count))
+                })
+                .collect()
+        }
+
+        /// approve or reject a comment, returning whether it exists
+        pub fn set_status(&self, id: i64, status: Status) -> rusqlite::Result<bool> {
+            set_status(&self.db.lock().unwrap(), id, status)
+        }
+
+        /// queue a comment for moderation
+        pub fn submit(
+            &self,
+            post_id: &str,
+            parent_id: Option<i64>,
+            author: &str,
+            body: &str,
+            client: IpAddr,
+        ) -> Result<(), SubmitError> {
+            let author = author.trim();
+            let body = body.trim();
+            if author.is_empty() || body.is_empty() {
+                return Err(SubmitError::Invalid(
+                    "Please fill in your name and a comment.",
+                ));
+            }
+            if author.chars().count() > MAX_AUTHOR_LENGTH {
+                return Err(SubmitError::Invalid("That name is too long."));
+            }
+            if body.chars().count() > MAX_BODY_LENGTH {
+                return Err(SubmitError::Invalid("That comment is too long."));
+            }
+
+            if !self.recent.record(client) {
+                return Err(SubmitError::RateLimited);
+            }
+
+            let db = self.db.lock().unwrap();
+            if let Some(parent_id) = parent_id {
+                let parent_ok = db
+                    .query_row(
+                        "SELECT 1 FROM comments WHERE id = ?1 AND post_id = ?2 AND status = ?3",
+                        params![parent_id, post_id, Status::Approved.to_string()],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                if !parent_ok {
+                    return Err(SubmitError::Invalid("That comment can't be replied to."));
+                }
+            }
+            db.execute(
+                "INSERT INTO comments (post_id, parent_id, author, body, created_at, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    post_id,
+                    parent_id,
+                    author,
+                    body,
+                    Utc::now(),
+                    Status::Pending.to_string()
+                ],
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Moderate from the command line, with `www comments` followed by
+    /// `pending` (the default), `approve <id>...`, or `reject <id>...`.
+    pub fn run_subcommand(args: &[String]) -> Result<(), String> {
+        let path = path_from_env().ok_or("set COMMENTS_DB to the comments database")?;
+        let db = open(&path).map_err(|e| e.to_string())?;
+
+        match args {
+            [] => print_pending(&db),
+            [command] if command == "pending" => print_pending(&db),
+            [command, ids @ ..] if !ids.is_empty() => {
+                let status = match command.as_str() {
+                    "approve" => Status::Approved,
+                    "reject" => Status::Rejected,
+                    _ => return Err(format!("unknown command `{command}`")),
+                };
+                for id in ids {
+                    let id = id
+                        .parse::<i64>()
+                        .map_err(|_| format!("invalid id `{id}`"))?;
+                    if set_status(&db, id, status).map_err(|e| e.to_string())? {
+                        println!("{id}: {status}");
+                    } else {
+                        println!("{id}: no such comment");
+                    }
+                }
+                Ok(())
+            }
+            _ => Err("usage: comments [pending | approve <id>... | reject <id>...]".to_string()),
+        }
+    }
+
+    fn print_pending(db: &Connection) -> Result<(), String> {
+        let pending = with_status(db, Status::Pending, None).map_err(|e| e.to_string())?;
+        for comment in &pending {
+            println!(
+                "#{} on {}{} by {} at {}",
+                comment.id,
+                comment.post_id,
+                comment
+                    .parent_id
+                    .map(|id| format!(", replying to #{id}"))
+                    .unwrap_or_default(),
+                comment.author,
+                comment.created_at.format("%Y-%m-%d %H:%M"),
+            );
+            for line in comment.body.lines() {
+                println!("    {line}");
+            }
+            println!();
+        }
+        println!("{} pending", pending.len());
+        Ok(())
+    }
+
+    /// Comment markdown as html, limited to paragraphs, emphasis, code, lists,
+    /// quotes, and http(s) links, which are marked nofollow. Raw html is shown
+    /// as text, and anything else is reduced to its text.
+    pub fn render(body: &str) -> String {
+        let parser =
+            Parser::new_ext(body, Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+                Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+                event => event,
+            });
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, parser);
+
+        ammonia::Builder::empty()
+            .add_tags([
+                "p",
+                "br",
+                "em",
+                "strong",
+                "del",
+                "code",
+                "pre",
+                "a",
+                "ul",
+                "ol",
+                "li",
+                "blockquote",
+            ])
+            .add_tag_attributes("a", ["href"])
+            .url_schemes(HashSet::from(["http", "https"]))
+            .url_relative(ammonia::UrlRelative::Deny)
+            .link_rel(Some("nofollow ugc noopener noreferrer"))
+            .clean(&html)
+            .to_string()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const CLIENT: IpAddr = IpAddr::V4(std::net::Ipv4Addr::LOCALHOST);
+
+        fn in_memory_store() -> Store {
+            let db = Connection::open_in_memory().unwrap();
+            db.execute_batch(SCHEMA).unwrap();
+            Store {
+                db: Mutex::new(db),
+                recent: RecentActivity::new(5, Duration::from_secs(60 * 60)),
+            }
+        }
+
+        fn invalid(result: Result<(), SubmitError>) -> &'static str {
+            match result {
+                Err(SubmitError::Invalid(message)) => message,
+                Ok(()) => panic!("expected an invalid comment, but it was accepted"),
+                Err(_) => panic!("expected an invalid comment, but it failed otherwise"),
+            }
+        }
+
+        #[test]
+        fn render_escapes_html() {
+            let html = render("<script>alert(1)</script>\n\nhi <b onclick=\"alert(1)\">there</b>");
+            assert!(!html.contains("<script"), "{html}");
+            assert!(!html.contains("<b"), "{html}");
+            assert!(html.contains("&lt;script&gt;"), "{html}");
+        }
+
+        #[test]
+        fn render_drops_unsafe_links() {
+            for body in [
+                "[click](javascript:alert(1))",
+                "[click](JavaScript:alert(1))",
+                "[click](/admin)",
+                "<javascript:alert(1)>",
+            ] {
+                let html = render(body);
+                assert!(!html.contains("href"), "{body} rendered as {html}");
+            }
+        }
+
+        #[test]
+        fn render_marks_links_nofollow() {
+            let html = render("see [my site](https://example.com)");
+            assert!(
+                html.contains(r#"<a href="https://example.com" rel="nofollow ugc noopener noreferrer">my site</a>"#),
+                "{html}"
+            );
+        }
+
+        #[test]
+        fn render_keeps_basic_markdown() {
+            let html = render("*hi* `code`\n\n- one\n\n> quoted\n\n# not a heading");
+            for tag in [
+                "<em>hi</em>",
+                "<code>code</code>",
+                "<li>one</li>",
+                "<blockquote>",
+            ] {
+                assert!(html.contains(tag), "{tag} missing from {html}");
+            }
+            assert!(!html.contains("<h1"), "{html}");
+        }
+
+        #[test]
+        fn submit_requires_author_and_body() {
+            let store = in_memory_store();
+            for (author, body) in [("", "hello"), ("me", ""), ("  ", "hello"), ("me", " \n ")] {
+                invalid(store.submit("post", None, author, body, CLIENT));
+            }
+            assert!(store.pending().unwrap().is_empty());
+        }
+
+        #[test]
+        fn submit_limits_lengths() {
+            let store = in_memory_store();
+            let long_author = "a".repeat(MAX_AUTHOR_LENGTH + 1);
+            let long_body = "b".repeat(MAX_BODY_LENGTH + 1);
+            invalid(store.submit("post", None, &long_author, "hello", CLIENT));
+            invalid(store.submit("post", None, "me", &long_body, CLIENT));
+
+            // lengths are in characters, not bytes
+            let author = "é".repeat(MAX_AUTHOR_LENGTH);
+            let body = "é".repeat(MAX_BODY_LENGTH);
+            assert!(store.submit("post", None, &author, &body, CLIENT).is_ok());
+        }
+
+        #[test]
+        fn submit_queues_trimmed_comments_for_moderation() {
+            let store = in_memory_store();
+            assert!(
+                store
+                    .submit("post", None, " me ", " hello\n", CLIENT)
+                    .is_ok()
+            );
+
+            let pending = store.pending().unwrap();
+            assert_eq!(pending.len(), 1);
+            assert_eq!(
+                (
+                    pending[0].post_id.as_str(),
+                    pending[0].author.as_str(),
+                    pending[0].body.as_str()
+                ),
+                ("post", "me", "hello")
+            );
+            assert!(store.approved("post").unwrap().is_empty());
+        }
+
+        #[test]
+        fn submit_only_replies_to_approved_comments_on_the_same_post() {
+            let store = in_memory_store();
+            assert!(store.submit("post", None, "me", "parent", CLIENT).is_ok());
+            let parent = store.pending().unwrap()[0].id;
+
+            // pending, missing, and on another post
+            invalid(store.submit("post", Some(parent), "you", "reply", CLIENT));
+            invalid(store.submit("post", Some(parent + 1), "you", "reply", CLIENT));
+            store.set_status(parent, Status::Approved).unwrap();
+            invalid(store.submit("other-post", Some(parent), "you", "reply", CLIENT));
+
+            assert!(
+                store
+                    .submit("post", Some(parent), "you", "reply", CLIENT)
+                    .is_ok()
+            );
+            assert_eq!(store.pending().unwrap()[0].parent_id, Some(parent));
+        }
+    }
+}
+
+// admin
+
+/// Authentication for the /admin dashboard, which is only enabled when
+/// `ADMIN_PASSWORD_HASH` is set to an argon2 hash of the admin password, as
+/// printed by `echo -n <password> | www hash-password`.
+///
+/// Logging in starts a session, identified by a random token in an `HttpOnly`,
+/// `SameSite=Strict` cookie. Every form that changes something also carries
+/// the session's CSRF token, which `AdminSession::verify_csrf` checks. Sessions
+/// are kept in memory, so restarting logs everyone out.
+mod admin {
+    use std::collections::HashMap;
+    use std::io::BufRead;
+    use std::net::IpAddr;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::{Duration, Instant};
+
+    use argon2::Argon2;
+    use argon2::password_hash::rand_core::OsRng;
+    use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+    use axum::extract::FromRequestParts;
+    use axum::http::request::Parts;
+    use axum::http::{HeaderMap, HeaderValue, header};
+    use axum::response::{IntoResponse, Redirect, Response};
+
+    use super::{RecentActivity, random_hex};
+
+    pub const SESSION_COOKIE: &str = "admin_session";
+    const SESSION_LIFETIME: Duration = Duration::from_secs(12 * 60 * 60);
+
+    static AUTH: OnceLock<Auth> = OnceLock::new();
+
+    pub struct Auth {
+        /// argon2 hash, in PHC string format
+        password_hash: String,
+        /// by session token
+        sessions: Mutex<HashMap<String, Session>>,
+        /// login attempts, to slow down password guessing
+        attempts: RecentActivity,
+    }
+
+    struct Session {
+        csrf_token: String,
+        expires: Instant,
+    }
+
+    pub enum LoginError {
+        RateLimited,
+        WrongPassword,
+    }
+
+    /// Enable the dashboard if `ADMIN_PASSWORD_HASH` is set, failing if it isn't
+    /// a valid password hash.
+    pub fn init_from_env() -> Result<(), String> {
+        let Ok(password_hash) = std::env::var("ADMIN_PASSWORD_HASH") else {
+            return Ok(());
+        };
+        PasswordHash::new(&password_hash)
+            .map_err(|e| format!("ADMIN_PASSWORD_HASH is not a password hash: {e}"))?;
+        let auth = Auth {
+            password_hash,
+            sessions: Mutex::new(HashMap::new()),
+            attempts: RecentActivity::new(10, Duration::from_secs(15 * 60)),
+        };
+        // already enabled is fine too
+        let _ = AUTH.set(auth);
+        Ok(())
+    }
+
+    /// the dashboard's authentication, if it is enabled
+    pub fn auth() -> Option<&'static Auth> {
+        AUTH.get()
+    }
+
+    impl Auth {
+        /// Check `password`, and start a session if it is right, returning its
+        /// token. This is deliberately slow, so call it from a blocking task.
+        pub fn log_in(&self, password: &str, client: IpAddr) -> Result<String, LoginError> {
+            if !self.attempts.record(client) {
+                return Err(LoginError::RateLimited);
+            }
+            let hash = PasswordHash::new(&self.password_hash).expect("checked by init_from_env");
+            if Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_err()
+            {
+                return Err(LoginError::WrongPassword);
+            }
+
+            let token = random_hex(32);
+            let mut sessions = self.sessions.lock().unwrap();
+            let now = Instant::now();
+            sessions.retain(|_, session| session.expires > now);
+            sessions.insert(
+                token.clone(),
+                Session {
+                    csrf_token: random_hex(32),
+                    expires: now + SESSION_LIFETIME,
+                },
+            );
+            Ok(token)
+        }
+
+        pub fn log_out(&self, session: &AdminSession) {
+            self.sessions.lock().unwrap().remove(&session.token);
+        }
+
+        fn session(&self, token: &str) -> Option<AdminSession> {
+            let sessions = self.sessions.lock().unwrap();
+            let session = sessions.get(token)?;
+            (session.expires > Instant::now()).then(|| AdminSession {
+                token: token.to_string(),
+                csrf_token: session.csrf_token.clone(),
+            })
+        }
+    }
+
+    /// A logged in admin. Handlers which take this redirect to the login page
+    /// if there isn't one, or 404 if the dashboard isn't enabled.
+    pub struct AdminSession {
+        token: String,
+        /// include this in forms as `csrf`
+        pub csrf_token: String,
+    }
+
+    impl AdminSession {
+        /// whether a submitted form's `csrf` field came from this session
+        pub fn verify_csrf(&self, form: &HashMap<String, String>) -> bool {
+            form.get("csrf")
+                .is_some_and(|token| constant_time_eq(token.as_bytes(), self.csrf_token.as_bytes()))
+        }
+    }
+
+    impl<S: Send + Sync> FromRequestParts<S> for AdminSession {
+        type Rejection = Response;
+
+        async fn from_request_parts(
+            parts: &mut Parts,
+            _state: &S,
+        ) -> Result<Self, Self::Rejection> {
+            let Some(auth) = auth() else {
+                return Err(super::not_found().await);
+            };
+            session_token(&parts.headers)
+                .and_then(|token| auth.session(token))
+                .ok_or_else(|| Redirect::to("/admin/login").into_response())
+        }
+    }
+
+    fn session_token(headers: &HeaderMap) -> Option<&str> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|cookies| cookies.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .find_map(|cookie| {
+                cookie
+                    .trim()
+                    .strip_prefix(SESSION_COOKIE)?
+                    .strip_prefix('=')
+            })
+    }
+
+    /// `Set-Cookie` value for a session, or to clear it if `None`
+    pub fn session_cookie(token: Option<&str>) -> HeaderValue {
+        let (token, max_age) = match token {
+            Some(token) => (token, SESSION_LIFETIME.as_secs()),
+            None => ("", 0),
+        };
+        HeaderValue::from_str(&format!(
+            "{SESSION_COOKIE}={token}; Path=/admin; Max-Age={max_age}; HttpOnly; Secure; SameSite=Strict"
+        ))
+        .unwrap()
+    }
+
+    fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    /// The `hash-password` subcommand, which hashes the password on the first
+    /// line of stdin for use as `ADMIN_PASSWORD_HASH`.
+    pub fn hash_password() -> Result<String, String> {
+        let mut password = String::new();
+        std::io::stdin()
+            .lock()
+            .read_line(&mut password)
+            .map_err(|e| e.to_string())?;
+        let password = password.trim_end_matches(['\r', '\n']);
+        if password.is_empty() {
+            return Err("no password given on stdin".to_string());
+        }
+        Argon2::default()
+            .hash_password(password.as_bytes(), &SaltString::generate(&mut OsRng))
+            .map(|hash| hash.to_string())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// analytics
+
+/// First-party page view counts, stored in SQLite at `ANALYTICS_DB` and only
+/// enabled when it is set.
+///
+/// Views are counted per day, path, and referring domain, without cookies or
+/// storing addresses. Unique visitors are counted by a hash of each visitor's
+/// address and user agent, salted with a random value which is only kept in
+/// memory and replaced every day, so visitors can't be identified or followed
+/// from one day to the next. (Restarting also replaces it, so a visitor seen
+/// both before and after a restart counts twice that day.)
+mod analytics {
+    use std::hash::{BuildHasher, RandomState};
+    use std::net::{IpAddr, SocketAddr};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Mutex, OnceLock};
+
+    use axum::extract::{ConnectInfo, Request};
+    use axum::http::{Method, header};
+    use axum::middleware::Next;
+    use axum::response::Response;
+    use chrono::{NaiveDate, Utc};
+    use rusqlite::{Connection, params};
+
+    const SCHEMA: &str = "
+        PRAGMA journal_mode = WAL;
+        CREATE TABLE IF NOT EXISTS page_views (
+            day TEXT NOT NULL,
+            path TEXT NOT NULL,
+            referrer TEXT NOT NULL,
+            views INTEGER NOT NULL,
+            PRIMARY KEY (day, path, referrer)
+        );
+        CREATE TABLE IF NOT EXISTS visitors (
+            day TEXT NOT NULL,
+            path TEXT NOT NULL,
+            referrer TEXT NOT NULL,
+            visitor INTEGER NOT NULL,
+            PRIMARY KEY (day, path, referrer, visitor)
+        );
+    ";
+
+    /// user agents containing these are crawlers, not readers
+    const BOT_USER_AGENTS: [&str; 4] = ["bot", "crawl", "spider", "slurp"];
+
+    static STORE: OnceLock<Store> = OnceLock::new();
+
+    pub struct Store {
+        db: Mutex<Connection>,
+        /// today's salt for visitor hashes, and the day it is for
+        salt: Mutex<(NaiveDate, RandomState)>,
+    }
+
+    struct PageView {
+        day: NaiveDate,
+        path: String,
+        /// domain of the referring page, or empty if there isn't one
+        referrer: String,
+        /// salted hash identifying the visitor, for today only
+        visitor: i64,
+    }
+
+    /// views and unique visitors of something, e.g. a path, over some days
+    pub struct Count {
+        pub key: String,
+        pub views: u64,
+        /// summed over each day, so a visitor who comes back counts again
+        pub visitors: u64,
+    }
+
+    /// `None` if analytics are not enabled
+    pub fn path_from_env() -> Option<PathBuf> {
+        std::env::var_os("ANALYTICS_DB").map(PathBuf::from)
+    }
+
+    /// open (creating if needed) the database at `path`, and enable analytics
+    pub fn init(path: &Path) -> rusqlite::Result<()> {
+        let db = Connection::open(path)?;
+        db.execute_batch(SCHEMA)?;
+        let store = Store {
+            db: Mutex::new(db),
+            salt: Mutex::new((Utc::now().date_naive(), RandomState::new())),
+        };
+        // already enabled is fine too
+        let _ = STORE.set(store);
+        Ok(())
+    }
+
+    /// the analytics store, if analytics are enabled
+    pub fn store() -> Option<&'static Store> {
+        STORE.get()
+    }
+
+    /// Middleware which counts successful, full page loads of html pages, other
+    /// than the admin dashboard's.
+    pub async fn track(request: Request, next: Next) -> Response {
+        let Some(store) = store() else {
+            return next.run(request).await;
+        };
+
+        let headers = request.headers();
+        let user_agent = headers
+            .get(header::USER_AGENT)
+            .and_then(|ua| ua.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let path = request.uri().path().to_string();
+        let is_bot = BOT_USER_AGENTS
+            .iter()
+            .any(|bot| user_agent.to_ascii_lowercase().contains(bot));
+        let counted = request.method() == Method::GET
+            && !headers.contains_key("HX-Request")
+            && !path.starts_with("/admin")
+            && !is_bot;
+        let referrer = headers
+            .get(header::REFERER)
+            .and_then(|referrer| referrer.to_str().ok())
+            .and_then(referrer_domain)
+            .unwrap_or_default();
+        let client = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| super::client_ip(headers, *peer));
+
+        let response = next.run(request).await;
+
+        let is_html = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/html"));
+        if let Some(client) = client
+            && counted
+            && is_html
+            && response.status().is_success()
+        {
+            let view = store.page_view(path, referrer, client, &user_agent);
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = store.record(&view) {
+                    tracing::error!("failed to record page view: {}", e);
+                }
+            });
+        }
+        response
+    }
+
+    /// the host of a referring url, unless it is this site
+    fn referrer_domain(referrer: &str) -> Option<String> {
+        let rest = referrer
+            .strip_prefix("https://")
+            .or_else(|| referrer.strip_prefix("http://"))?;
+        let host = rest.split(['/', '?', '#']).next()?;
+        let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+        let host = host.split(':').next()?.to_ascii_lowercase();
+        let own_host = super::SITE_URL.split("://").nth(1).unwrap_or_default();
+        (!host.is_empty() && host != own_host).then_some(host)
+    }
+
+    impl Store {
+        /// check that the database responds
+        pub fn ping(&self) -> rusqlite::Result<()> {
+            self.db
+                .lock()
+                .unwrap()
+                .query_row("SELECT 1", [], |_| Ok(()))
+        }
+
+        fn page_view(
+            &self,
+            path: String,
+            referrer: String,
+            client: IpAddr,
+            user_agent: &str,
+        ) -> PageView {
+            let mut salt = self.salt.lock().unwrap();
+            let today = Utc::now().date_naive();
+            if salt.0 != today {
+                *salt = (today, RandomState::new());
+            }
+            PageView {
+                day: today,
+                path,
+                referrer,
+                visitor: salt.1.hash_one((client, user_agent)) as i64,
+            }
+        }
+
+        fn record(&self, view: &PageView) -> rusqlite::Result<()> {
+            let db = self.db.lock().unwrap();
+            db.execute(
+                "INSERT INTO page_views (day, path, referrer, views) VALUES (?1, ?2, ?3, 1)
+                 ON CONFLICT DO UPDATE SET views = views + 1",
+                params![view.day, view.path, view.referrer],
+            )?;
+            db.execute(
+                "INSERT OR IGNORE INTO visitors (day, path, referrer, visitor) VALUES (?1, ?2, ?3, ?4)",
+                params![view.day, view.path, view.referrer, view.visitor],
+            )?;
+            Ok(())
+        }
+
+        /// views and visitors since `since` grouped by `column`, most viewed
+        /// first
+        fn grouped(&self, column: &str, since: NaiveDate) -> rusqlite::Result<Vec<Count>> {
+            let db = self.db.lock().unwrap();
+            db.prepare(&format!(
+                "SELECT p.{column}, SUM(p.views),
+                     (SELECT COUNT(*) FROM (
+                         SELECT DISTINCT day, visitor FROM visitors v
+                         WHERE v.day >= ?1 AND v.{column} = p.{column}
+                     ))
+                 FROM page_views p
+                 WHERE p.day >= ?1
+                 GROUP BY p.{column}
+                 ORDER BY 2 DESC, 1"
+            ))?
+            .query_map([since], count)?
+            .collect()
+        }
+
+        /// per day since `since`, oldest first; visitors are unique that day
+        pub fn days(&self, since: NaiveDate) -> rusqlite::Result<Vec<Count>> {
+            let db = self.db.lock().unwrap();
+            db.prepare(
+                "SELECT day, SUM(views),
+                     (SELECT COUNT(DISTINCT visitor) FROM visitors v WHERE v.day = p.day)
+                 FROM page_views p
+                 WHERE day >= ?1
+                 GROUP BY day
+                 ORDER BY day",
+            )?
+            .query_map([since], count)?
+            .collect()
+        }
+
+        /// per path since `since`, most viewed first
+        pub fn paths(&self, since: NaiveDate) -> rusqlite::Result<Vec<Count>> {
+            self.grouped("path", since)
+        }
+
+        /// per referring domain since `since`, most viewed first, excluding
+        /// views without a referrer
+        pub fn referrers(&self, since: NaiveDate) -> rusqlite::Result<Vec<Count>> {
+            let mut referrers = self.grouped("referrer", since)?;
+            referrers.retain(|referrer| !referrer.key.is_empty());
+            Ok(referrers)
+        }
+
+        /// Every count since `since` as CSV, with the columns day, path,
+        /// referrer, views, and visitors.
+        pub fn csv(&self, since: NaiveDate) -> rusqlite::Result<String> {
+            let db = self.db.lock().unwrap();
+            let mut csv = String::from("day,path,referrer,views,visitors\r\n");
+            let mut statement = db.prepare(
+                "SELECT day, path, referrer, views,
+                     (SELECT COUNT(*) FROM visitors v
+                      WHERE v.day = p.day AND v.path = p.path AND v.referrer = p.referrer)
+                 FROM page_views p
+                 WHERE day >= ?1
+                 ORDER BY day, path, referrer",
+            )?;
+            let mut rows = statement.query([since])?;
+            while let Some(row) = rows.next()? {
+                let fields: [String; 5] = [
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get::<_, u64>(3)?.to_string(),
+                    row.get::<_, u64>(4)?.to_string(),
+                ];
+                let fields = fields
+                    .iter()
+                    .map(|field| csv_field(field))
+                    .collect::<Vec<_>>();
+                csv.push_str(&fields.join(","));
+                csv.push_str("\r\n");
+            }
+            Ok(csv)
+        }
+    }
+
+    fn count(row: &rusqlite::Row) -> rusqlite::Result<Count> {
+        Ok(Count {
+            key: row.get(0)?,
+            views: row.get(1)?,
+            visitors: row.get(2)?,
+        })
+    }
+
+    /// quote a CSV field if it needs it
+    fn csv_field(field: &str) -> String {
+        if field.contains([',', '"', '\r', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+// metrics
+
+/// Prometheus metrics, served at /metrics in the text exposition format only to
+/// clients connecting from `TRUSTED_PROXIES` without forwarding headers, such
+/// as a scraper on the same host.
+/// The default registry also reports process stats (CPU, memory, open files).
+mod metrics {
+    use std::time::Instant;
+
+    use axum::extract::{MatchedPath, Request};
+    use axum::middleware::Next;
+    use axum::response::Response;
+    use lazy_static::lazy_static;
+    use prometheus::{
+        Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+        register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+        register_int_gauge_vec,
+    };
+
+    lazy_static! {
+        static ref REQUESTS: IntCounterVec = register_int_counter_vec!(
+            "http_requests_total",
+            "HTTP requests handled, by route, method, and status class (e.g. 2xx)",
+            &["route", "method", "status"]
+        )
+        .unwrap();
+        static ref REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+            "http_request_duration_seconds",
+            "Time to handle HTTP requests, by route and method",
+            &["route", "method"]
+        )
+        .unwrap();
+        static ref REQUESTS_IN_FLIGHT: IntGauge = register_int_gauge!(
+            "http_requests_in_flight",
+            "HTTP requests currently being handled"
+        )
+        .unwrap();
+        static ref STATIC_BYTES: IntCounter =
+            register_int_counter!("static_asset_bytes_total", "Bytes of static assets served")
+                .unwrap();
+        static ref CSP_REPORTS: IntCounter = register_int_counter!(
+            "csp_reports_total",
+            "Content-Security-Policy violation reports received"
+        )
+        .unwrap();
+        static ref THROTTLED: IntCounterVec = register_int_counter_vec!(
+            "http_requests_throttled_total",
+            "HTTP requests rejected for exceeding their client's rate limit, by budget",
+            &["budget"]
+        )
+        .unwrap();
+        static ref BUILD: IntGaugeVec = register_int_gauge_vec!(
+            "www_build_info",
+            "Always 1, labelled with the version and git commit of this build",
+            &["version", "commit"]
+        )
+        .unwrap();
+    }
+
+    /// Middleware which counts and times requests. Routes are labelled by
+    /// their pattern, e.g. `/posts/{index}/{id}`, so that the number of
+    /// series stays bounded; requests which matched no route are `unmatched`.
+    pub async fn track(request: Request, next: Next) -> Response {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or("unmatched", |path| path.as_str())
+            .to_string();
+        let method = request.method().to_string();
+
+        let _in_flight = InFlight::start();
+        let start = Instant::now();
+        let response = next.run(request).await;
+        REQUEST_DURATION
+            .with_label_values(&[&route, &method])
+            .observe(start.elapsed().as_secs_f64());
+
+        let status = format!("{}xx", response.status().as_u16() / 100);
+        REQUESTS
+            .with_label_values(&[&route, &method, &status])
+            .inc();
+        response
+    }
+
+    /// counts a request in flight until dropped, even if the client goes away
+    /// before it is handled
+    struct InFlight;
+
+    impl InFlight {
+        fn start() -> Self {
+            REQUESTS_IN_FLIGHT.inc();
+            InFlight
+        }
+    }
+
+    impl Drop for InFlight {
+        fn drop(&mut self) {
+            REQUESTS_IN_FLIGHT.dec();
+        }
+    }
+
+    pub fn throttled(budget: &str) {
+        THROTTLED.with_label_values(&[budget]).inc();
+    }
+
+    pub fn csp_report() {
+        CSP_REPORTS.inc();
+    }
+
+    pub fn static_asset_served(bytes: usize) {
+        STATIC_BYTES.inc_by(bytes as u64);
+    }
+
+    /// every metric, in the Prometheus text format
+    pub fn render() -> String {
+        BUILD
+            .with_label_values(&[super::BUILD_INFO.version, &super::BUILD_INFO.commit()])
+            .set(1);
+
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&prometheus::gather(), &mut buffer)
+            .expect("metrics are valid");
+        String::from_utf8(buffer).expect("metrics are utf-8")
+    }
+}
+
+// health
+
+/// Liveness and readiness, for container healthchecks. Readiness waits on
+/// self-checks of the compiled-in content, which run once at startup, and on
+/// the databases responding.
+mod health {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    use lazy_static::lazy_static;
+    use pulldown_cmark::{Event, Parser, Tag};
+    use serde_json::{Value, json};
+
+    use super::{Assets, BUILD_INFO, HOMEPAGE, POSTS, PROJECTS, RESUME, SERIES};
+
+    lazy_static! {
+        static ref STARTED: Instant = Instant::now();
+    }
+
+    /// results of `run_self_checks`, once it has finished
+    static SELF_CHECKS: OnceLock<Vec<Check>> = OnceLock::new();
+
+    struct Check {
+        name: &'static str,
+        /// what went wrong, if anything
+        failures: Vec<String>,
+    }
+
+    impl Check {
+        fn to_json(&self) -> Value {
+            json!({
+                "name": self.name,
+                "ok": self.failures.is_empty(),
+                "failures": self.failures,
+            })
+        }
+    }
+
+    /// start counting uptime
+    pub fn start() {
+        lazy_static::initialize(&STARTED);
+    }
+
+    /// Check that every post renders, as html and PDF, that the résumé PDF
+    /// renders, and that every site-relative image resolves to an asset. This
+    /// takes a moment, so call it from a blocking task.
+    pub fn run_self_checks() {
+        let renders = |name: &'static str, render: &dyn Fn() -> Result<(), String>| {
+            catch_unwind(AssertUnwindSafe(render))
+                .unwrap_or_else(|_| Err("panicked".to_string()))
+                .err()
+                .map(|e| format!("{name}: {e}"))
+        };
+
+        let posts = Check {
+            name: "posts render",
+            failures: POSTS
+                .iter()
+                .filter_map(|post| {
+                    renders(post.id, &|| {
+                        super::post_page_markup(post, Some(&[]));
+                        super::pdf::post(post, &super::absolute_url("/posts"))
+                            .map(drop)
+                            .map_err(|e| e.to_string())
+                    })
+                })
+                .collect(),
+        };
+
+        let resume = Check {
+            name: "résumé renders",
+            failures: renders("resume", &|| {
+                super::resume_page_markup();
+                super::pdf::resume(&RESUME, &super::absolute_url("/resume"))
+                    .map(drop)
+                    .map_err(|e| e.to_string())
+            })
+            .into_iter()
+            .collect(),
+        };
+
+        let mut images: Vec<(&str, String)> = vec![];
+        for post in POSTS.iter() {
+            for event in Parser::new(post.content) {
+                if let Event::Start(Tag::Image { dest_url, .. }) = event {
+                    images.push((post.id, dest_url.to_string()));
+                }
+            }
+        }
+        for project in PROJECTS.iter() {
+            images.extend(
+                project
+                    .screenshots
+                    .iter()
+                    .map(|s| (project.id, s.src.to_string())),
+            );
+        }
+        images.extend(
+            HOMEPAGE
+                .headshot
+                .iter()
+                .map(|h| ("homepage", h.src.to_string())),
+        );
+        let images = Check {
+            name: "images resolve",
+            failures: images
+                .into_iter()
+                // images elsewhere can't be checked offline
+                .filter(|(_, src)| src.starts_with('/'))
+                .filter(|(_, src)| src.strip_prefix("/static/").and_then(Assets::get).is_none())
+                .map(|(page, src)| format!("{page}: {src}"))
+                .collect(),
+        };
+
+        let checks = vec![posts, resume, images];
+        for check in &checks {
+            for failure in &check.failures {
+                tracing::error!("self-check {:?} failed: {}", check.name, failure);
+            }
+        }
+        let _ = SELF_CHECKS.set(checks);
+    }
+
+    fn build() -> Value {
+        json!({
+            "version": BUILD_INFO.version,
+            "commit": BUILD_INFO.git_sha,
+            "dirty": BUILD_INFO.dirty,
+            "built_at": BUILD_INFO.built_at().to_rfc3339(),
+            "uptime_seconds": STARTED.elapsed().as_secs(),
+        })
+    }
+
+    /// the server is up and handling requests
+    pub fn live() -> Value {
+        json!({
+            "status": "alive",
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "build": build(),
+        })
+    }
+
+    /// Whether the server is ready to serve: the self-checks have finished
+    /// and passed, and the databases which are enabled respond. Also reports
+    /// how much content is compiled in.
+    pub fn ready() -> (bool, Value) {
+        let mut checks = vec![];
+        if let Some(store) = super::comments::store() {
+            checks.push(Check {
+                name: "comments database",
+                failures: store
+                    .ping()
+                    .err()
+                    .map(|e| e.to_string())
+                    .into_iter()
+                    .collect(),
+            });
+        }
+        if let Some(store) = super::analytics::store() {
+            checks.push(Check {
+                name: "analytics database",
+                failures: store
+                    .ping()
+                    .err()
+                    .map(|e| e.to_string())
+                    .into_iter()
+                    .collect(),
+            });
+        }
+
+        let self_checks = SELF_CHECKS.get();
+        let ready = self_checks.is_some_and(|checks| checks.iter().all(|c| c.failures.is_empty()))
+            && checks.iter().all(|c| c.failures.is_empty());
+        let status = match (ready, self_checks) {
+            (true, _) => "ready",
+            (false, None) => "starting",
+            (false, Some(_)) => "not ready",
+        };
+
+        let checks = self_checks
+            .into_iter()
+            .flatten()
+            .chain(&checks)
+            .map(Check::to_json)
+            .collect::<Vec<_>>();
+        let body = json!({
+            "status": status,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "build": build(),
+            "content": {
+                "posts": POSTS.len(),
+                "series": SERIES.len(),
+                "projects": PROJECTS.len(),
+                "assets": Assets::iter().count(),
+            },
+            "checks": checks,
+        });
+        (ready, body)
+    }
+}
+
+// access log
+
+/// Access logging, request ids, and working out which client a request came
+/// from.
+///
+/// Every request gets an id, taken from its `X-Request-Id` or Cloudflare
+/// `CF-Ray` header when it comes from a trusted proxy or else generated, which
+/// is echoed in the response's `X-Request-Id` header and included in its trace
+/// span and access log event.
+/// Set `LOG_FORMAT=json` to log JSON lines rather than text.
+///
+/// Only proxies on the same host are trusted by default. Behind proxies
+/// elsewhere, e.g. cloudflared in another container, set `TRUSTED_PROXIES` to
+/// their addresses, and behind Cloudflare also set
+/// `TRUSTED_PROXY_HEADER=CF-Connecting-IP`.
+mod access_log {
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    use axum::extract::{ConnectInfo, Request};
+    use axum::http::{HeaderMap, HeaderName, HeaderValue, header};
+    use axum::middleware::Next;
+    use axum::response::Response;
+
+    pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+    /// proxies trusted by default: only ones on the same host
+    const DEFAULT_TRUSTED_PROXIES: &str = "127.0.0.0/8,::1/128";
+
+    static PROXIES: OnceLock<Proxies> = OnceLock::new();
+
+    /// identifies a request in logs; stored in the request's extensions
+    #[derive(Clone)]
+    pub struct RequestId(pub String);
+
+    /// an address range, e.g. `10.0.0.0/8`
+    struct IpNetwork {
+        address: IpAddr,
+        prefix: u32,
+    }
+
+    impl IpNetwork {
+        fn parse(network: &str) -> Option<Self> {
+            let (address, prefix) = match network.split_once('/') {
+                Some((address, prefix)) => (address.parse::<IpAddr>().ok()?, prefix.parse().ok()?),
+                None => {
+                    let address = network.parse::<IpAddr>().ok()?;
+                    (address, if address.is_ipv4() { 32 } else { 128 })
+                }
+            };
+            let bits = if address.is_ipv4() { 32 } else { 128 };
+            (prefix <= bits).then_some(IpNetwork { address, prefix })
+        }
+
+        fn contains(&self, ip: IpAddr) -> bool {
+            let mask = |bits: u32| match self.prefix {
+                0 => 0,
+                prefix => u128::MAX << (bits - prefix),
+            };
+            match (self.address, ip.to_canonical()) {
+                (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                    let mask = mask(32) as u32;
+                    u32::from(network) & mask == u32::from(ip) & mask
+                }
+                (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                    let mask = mask(128);
+                    u128::from(network) & mask == u128::from(ip) & mask
+                }
+                _ => false,
+            }
+        }
+    }
+
+    /// the proxies in front of the site, and what they say about requests
+    struct Proxies {
+        trusted: Vec<IpNetwork>,
+        /// a header holding just the visitor's address, set by the outermost
+        /// proxy, e.g. Cloudflare's `CF-Connecting-IP`
+        client_ip_header: Option<HeaderName>,
+    }
+
+    impl Proxies {
+        fn is_trusted(&self, ip: IpAddr) -> bool {
+            self.trusted.iter().any(|network| network.contains(ip))
+        }
+
+        fn client_ip(&self, headers: &HeaderMap, peer: SocketAddr) -> IpAddr {
+            let peer = peer.ip().to_canonical();
+            if !self.is_trusted(peer) {
+                return peer;
+            }
+            if let Some(ip) = self
+                .client_ip_header
+                .as_ref()
+                .and_then(|name| headers.get(name)?.to_str().ok()?.trim().parse().ok())
+            {
+                return ip;
+            }
+
+            // each proxy appends the address it received the request from, so
+            // everything left of the last untrusted one could be forged
+            let forwarded = headers
+                .get_all("X-Forwarded-For")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+                .collect::<Vec<_>>();
+            forwarded
+                .iter()
+                .rev()
+                .find(|ip| !self.is_trusted(**ip))
+                .or(forwarded.first())
+                .copied()
+                .unwrap_or(peer)
+        }
+
+        /// whether a proxy has said who the request is for
+        fn is_forwarded(&self, headers: &HeaderMap) -> bool {
+            headers.contains_key("X-Forwarded-For")
+                || headers.contains_key(header::FORWARDED)
+                || self
+                    .client_ip_header
+                    .as_ref()
+                    .is_some_and(|name| headers.contains_key(name))
+        }
+    }
+
+    /// Read the proxies whose forwarding headers are trusted from
+    /// `TRUSTED_PROXIES`, a comma separated list of addresses and ranges, e.g.
+    /// `127.0.0.1,172.16.0.0/12`, and the header in which the outermost proxy
+    /// puts the visitor's address, if any, from `TRUSTED_PROXY_HEADER`, e.g.
+    /// `CF-Connecting-IP`. Fails if either is invalid.
+    pub fn init_from_env() -> Result<(), String> {
+        let trusted = std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_else(|_| DEFAULT_TRUSTED_PROXIES.to_string());
+        let trusted = trusted
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .map(|proxy| {
+                IpNetwork::parse(proxy).ok_or(format!("invalid TRUSTED_PROXIES entry `{proxy}`"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let client_ip_header = std::env::var("TRUSTED_PROXY_HEADER")
+            .ok()
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                HeaderName::try_from(name.trim())
+                    .map_err(|_| format!("invalid TRUSTED_PROXY_HEADER `{name}`"))
+            })
+            .transpose()?;
+        // already set is fine too
+        let _ = PROXIES.set(Proxies {
+            trusted,
+            client_ip_header,
+        });
+        Ok(())
+    }
+
+    /// whether `ip` is in one of the `TRUSTED_PROXIES` ranges
+    pub fn is_trusted(ip: IpAddr) -> bool {
+        PROXIES.get().is_some_and(|proxies| proxies.is_trusted(ip))
+    }
+
+    /// Whether a request comes straight from a trusted host, rather than from
+    /// anyone through a trusted proxy.
+    pub fn is_trusted_direct(headers: &HeaderMap, peer: SocketAddr) -> bool {
+        PROXIES.get().is_some_and(|proxies| {
+            proxies.is_trusted(peer.ip().to_canonical()) && !proxies.is_forwarded(headers)
+        })
+    }
+
+    /// The address a request came from. If the peer is a trusted proxy, this is
+    /// the visitor's address from the `TRUSTED_PROXY_HEADER`, if one is set and
+    /// present, or else the last untrusted address in `X-Forwarded-For`;
+    /// otherwise it is the peer, since anyone else could forge those headers.
+    pub fn client_ip(headers: &HeaderMap, peer: SocketAddr) -> IpAddr {
+        match PROXIES.get() {
+            Some(proxies) => proxies.client_ip(headers, peer),
+            None => peer.ip().to_canonical(),
+        }
+    }
+
+    /// a request id from a header, if it's a reasonable one
+    fn header_request_id(headers: &HeaderMap, name: &str) -> Option<String> {
+        let id = headers.get(name)?.to_str().ok()?;
+        let reasonable = (1..=128).contains(&id.len())
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+        reasonable.then(|| id.to_string())
+    }
+
+    /// Middleware which gives each request an id, then logs it once it has
+    /// been handled.
+    pub async fn track(mut request: Request, next: Next) -> Response {
+        let start = Instant::now();
+        let headers = request.headers();
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(peer)| *peer);
+        // only a trusted proxy's ids are kept, so that no one else can make
+        // their requests share an id with someone else's in the logs
+        let request_id = peer
+            .filter(|peer| is_trusted(peer.ip().to_canonical()))
+            .and_then(|_| {
+                header_request_id(headers, REQUEST_ID_HEADER)
+                    .or_else(|| header_request_id(headers, "CF-Ray"))
+            })
+            .unwrap_or_else(|| super::random_hex(16));
+        let client = peer.map(|peer| client_ip(headers, peer));
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let user_agent = header(header::USER_AGENT);
+        let referrer = header(header::REFERER);
+        let method = request.method().clone();
+        let uri = request.uri().clone();
+        request
+            .extensions_mut()
+            .insert(RequestId(request_id.clone()));
+
+        let mut response = next.run(request).await;
+
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            response.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+        tracing::info!(
+            target: "www::access",
+            request_id,
+            client_ip = client.map(|ip| ip.to_string()),
+            method = %method,
+            uri = %uri,
+            status = response.status().as_u16(),
+            duration_ms = start.elapsed().as_secs_f64() * 1000.0,
+            user_agent,
+            referrer,
+            "{} {} {}",
+            method,
+            uri,
+            response.status().as_u16(),
+        );
+        response
+    }
+
+    /// the trace span for a request, which includes its id
+    pub fn span(request: &Request) -> tracing::Span {
+        let request_id = request
+            .extensions()
+            .get::<RequestId>()
+            .map(|RequestId(id)| id.as_str())
+            .unwrap_or_default();
+        let span = tracing::info_span!(
+            "request",
+            request_id,
+            method = %request.method(),
+            uri = %request.uri(),
+        );
+        #[cfg(feature = "otel")]
+        super::otel::set_parent(&span, request.headers());
+        span
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn ip(ip: &str) -> IpAddr {
+            ip.parse().unwrap()
+        }
+
+        fn proxies(trusted: &[&str], client_ip_header: Option<&str>) -> Proxies {
+            Proxies {
+                trusted: trusted
+                    .iter()
+                    .map(|network| IpNetwork::parse(network).unwrap())
+                    .collect(),
+                client_ip_header: client_ip_header.map(|name| HeaderName::try_from(name).unwrap()),
+            }
+        }
+
+        fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.append(*name, value.parse().unwrap());
+            }
+            headers
+        }
+
+        #[test]
+        fn parses_networks() {
+            for valid in [
+                "10.0.0.0/8",
+                "0.0.0.0/0",
+                "1.2.3.4/32",
+                "1.2.3.4",
+                "::/0",
+                "::1/128",
+                "::1",
+            ] {
+                assert!(IpNetwork::parse(valid).is_some(), "{valid}");
+            }
+            for invalid in [
+                "",
+                "10.0.0.0/33",
+                "::/129",
+                "10.0.0.0/",
+                "10.0.0/8",
+                "/8",
+                "localhost",
+            ] {
+                assert!(IpNetwork::parse(invalid).is_none(), "{invalid}");
+            }
+            assert_eq!(IpNetwork::parse("1.2.3.4").unwrap().prefix, 32);
+            assert_eq!(IpNetwork::parse("::1").unwrap().prefix, 128);
+        }
+
+        #[test]
+        fn matches_prefixes() {
+            let everything = IpNetwork::parse("0.0.0.0/0").unwrap();
+            assert!(everything.contains(ip("255.255.255.255")));
+            assert!(!everything.contains(ip("::1")));
+            assert!(
+                IpNetwork::parse("::/0")
+                    .unwrap()
+                    .contains(ip("2001:db8::1"))
+            );
+
+            let single = IpNetwork::parse("192.168.1.1/32").unwrap();
+            assert!(single.contains(ip("192.168.1.1")));
+            assert!(!single.contains(ip("192.168.1.2")));
+
+            let single = IpNetwork::parse("2001:db8::1/128").unwrap();
+            assert!(single.contains(ip("2001:db8::1")));
+            assert!(!single.contains(ip("2001:db8::2")));
+
+            let private = IpNetwork::parse("172.16.0.0/12").unwrap();
+            assert!(private.contains(ip("172.31.255.255")));
+            assert!(!private.contains(ip("172.32.0.0")));
+        }
+
+        #[test]
+        fn matches_ipv4_mapped_addresses() {
+            let loopback = IpNetwork::parse("127.0.0.0/8").unwrap();
+            assert!(loopback.contains(ip("::ffff:127.0.0.1")));
+            assert!(!loopback.contains(ip("::ffff:10.0.0.1")));
+        }
+
+        #[test]
+        fn ignores_headers_from_untrusted_peers() {
+            let proxies = proxies(&["127.0.0.1"], Some("CF-Connecting-IP"));
+            let forged = headers(&[
+                ("CF-Connecting-IP", "1.1.1.1"),
+                ("X-Forwarded-For", "2.2.2.2"),
+            ]);
+            let peer = "203.0.113.7:1234".parse().unwrap();
+            assert_eq!(proxies.client_ip(&forged, peer), ip("203.0.113.7"));
+        }
+
+        #[test]
+        fn reads_the_client_ip_header_only_when_configured() {
+            let peer = "127.0.0.1:1234".parse().unwrap();
+            let headers = headers(&[
+                ("CF-Connecting-IP", "1.1.1.1"),
+                ("X-Forwarded-For", "2.2.2.2"),
+            ]);
+            assert_eq!(
+                proxies(&["127.0.0.1"], Some("CF-Connecting-IP")).client_ip(&headers, peer),
+                ip("1.1.1.1")
+            );
+            assert_eq!(
+                proxies(&["127.0.0.1"], None).client_ip(&headers, peer),
+                ip("2.2.2.2")
+            );
+        }
+
+        #[test]
+        fn takes_the_last_untrusted_forwarded_address() {
+            let proxies = proxies(&["127.0.0.1", "10.0.0.0/8"], None);
+            let peer = "127.0.0.1:1234".parse().unwrap();
+
+            // a visitor's own forged entry is to the left of the address the
+            // first trusted proxy saw
+            let forwarded = headers(&[("X-Forwarded-For", "6.6.6.6, 203.0.113.7, 10.0.0.2")]);
+            assert_eq!(proxies.client_ip(&forwarded, peer), ip("203.0.113.7"));
+
+            // across repeated headers too
+            let forwarded = headers(&[
+                ("X-Forwarded-For", "6.6.6.6"),
+                ("X-Forwarded-For", "203.0.113.7, 10.0.0.2"),
+            ]);
+            assert_eq!(proxies.client_ip(&forwarded, peer), ip("203.0.113.7"));
+
+            // all trusted: the request started inside the network
+            let forwarded = headers(&[("X-Forwarded-For", "10.0.0.3, 10.0.0.2")]);
+            assert_eq!(proxies.client_ip(&forwarded, peer), ip("10.0.0.3"));
+
+            // nothing usable: the peer itself
+            let forwarded = headers(&[("X-Forwarded-For", "unknown")]);
+            assert_eq!(proxies.client_ip(&forwarded, peer), ip("127.0.0.1"));
+        }
+
+        #[test]
+        fn only_direct_requests_are_unforwarded() {
+            let proxies = proxies(&["127.0.0.1"], Some("CF-Connecting-IP"));
+            assert!(!proxies.is_forwarded(&HeaderMap::new()));
+            for name in ["X-Forwarded-For", "Forwarded", "CF-Connecting-IP"] {
+                assert!(
+                    proxies.is_forwarded(&headers(&[(name, "1.1.1.1")])),
+                    "{name}"
+                );
+            }
+        }
+    }
+}
+
+// opentelemetry
+
+/// Export of traces to an OpenTelemetry collector, over OTLP/HTTP, which is
+/// compiled in by the `otel` feature and enabled by setting
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` (e.g. `http://localhost:4318`). The other
+/// standard `OTEL_*` variables, like `OTEL_EXPORTER_OTLP_HEADERS`, apply too.
+///
+/// Requests carrying a W3C `traceparent` header continue that trace, so the
+/// site's spans join those of whatever is in front of it.
+///
+/// To look at traces locally, run a collector with a UI, e.g.
+/// `docker run --rm -p 4318:4318 -p 16686:16686 jaegertracing/all-in-one`,
+/// then `OTEL_EXPORTER_OTLP_ENDPOINT=http://localhost:4318 cargo run --features otel`
+/// and open <http://localhost:16686>. tests/otel.rs checks the export against
+/// a stand-in collector.
+#[cfg(feature = "otel")]
+mod otel {
+    use axum::http::HeaderMap;
+    use opentelemetry::KeyValue;
+    use opentelemetry::propagation::Extractor;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::SpanExporter;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+    use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+
+    /// `None` if `OTEL_EXPORTER_OTLP_ENDPOINT` is not set
+    pub fn provider_from_env() -> Result<Option<SdkTracerProvider>, String> {
+        if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_none() {
+            return Ok(None);
+        }
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .build()
+            .map_err(|e| format!("failed to build OTLP exporter: {e}"))?;
+        let resource = Resource::builder()
+            .with_service_name("www")
+            .with_attribute(KeyValue::new("service.version", super::BUILD_INFO.version))
+            .with_attribute(KeyValue::new(
+                "vcs.ref.head.revision",
+                super::BUILD_INFO.commit(),
+            ))
+            .build();
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource)
+            .build();
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        Ok(Some(provider))
+    }
+
+    /// the `tracing` layer which exports spans to `provider`
+    pub fn layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+    where
+        S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("www"))
+    }
+
+    /// continue the trace in a request's `traceparent` header, if it has one
+    pub fn set_parent(span: &tracing::Span, headers: &HeaderMap) {
+        let context = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        if let Err(e) = span.set_parent(context) {
+            tracing::debug!("failed to continue trace: {}", e);
+        }
+    }
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key)?.to_str().ok()
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|name| name.as_str()).collect()
+        }
+    }
+}
+
+// security headers
+
+/// Security headers for every response, including a Content-Security-Policy
+/// which only allows the site's own scripts, and those whose hashes build.rs
+/// computed. Set `CSP_REPORT_ONLY=1` to report violations to /csp-report
+/// without enforcing the policy, e.g. to try out a change to it. Reports are
+/// counted in /metrics, and logged at debug level (`RUST_LOG=www::csp=debug`).
+mod security {
+    use std::sync::OnceLock;
+
+    use axum::extract::Request;
+    use axum::http::{HeaderName, HeaderValue, header};
+    use axum::middleware::Next;
+    use axum::response::Response;
+
+    pub const CSP_REPORT_PATH: &str = "/csp-report";
+
+    static HEADERS: OnceLock<Vec<(HeaderName, HeaderValue)>> = OnceLock::new();
+
+    /// `'unsafe-inline'` styles are allowed since htmx adds a `<style>` for
+    /// its request indicators, and some markup sets `style` attributes.
+    fn content_security_policy() -> String {
+        let script_hashes = super::SCRIPT_HASHES
+            .iter()
+            .map(|(_, hash)| format!("'{hash}'"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        [
+            "default-src 'self'".to_string(),
+            format!("script-src 'self' {script_hashes}"),
+            "style-src 'self' 'unsafe-inline'".to_string(),
+            "img-src 'self'".to_string(),
+            "object-src 'none'".to_string(),
+            "base-uri 'self'".to_string(),
+            "form-action 'self'".to_string(),
+            "frame-ancestors 'none'".to_string(),
+            format!("report-uri {CSP_REPORT_PATH}"),
+            "report-to csp".to_string(),
+        ]
+        .join("; ")
+    }
+
+    fn headers() -> &'static [(HeaderName, HeaderValue)] {
+        HEADERS.get_or_init(|| {
+            let report_only = std::env::var("CSP_REPORT_ONLY").is_ok_and(|v| v == "1");
+            let csp_header = if report_only {
+                header::CONTENT_SECURITY_POLICY_REPORT_ONLY
+            } else {
+                header::CONTENT_SECURITY_POLICY
+            };
+            let value = |value: &str| HeaderValue::from_str(value).unwrap();
+            vec![
+                (csp_header, value(&content_security_policy())),
+                (
+                    HeaderName::from_static("reporting-endpoints"),
+                    value(&format!("csp=\"{CSP_REPORT_PATH}\"")),
+                ),
+                (
+                    header::STRICT_TRANSPORT_SECURITY,
+                    value("max-age=63072000"),
+                ),
+                (header::X_CONTENT_TYPE_OPTIONS, value("nosniff")),
+                (header::X_FRAME_OPTIONS, value("DENY")),
+                (
+                    header::REFERRER_POLICY,
+                    value("strict-origin-when-cross-origin"),
+                ),
+                (
+                    HeaderName::from_static("permissions-policy"),
+                    value("camera=(), microphone=(), geolocation=(), payment=(), usb=(), browsing-topics=()"),
+                ),
+            ]
+        })
+    }
+
+    /// Middleware which adds the security headers to responses, leaving any a
+    /// handler set itself.
+    pub async fn headers_layer(request: Request, next: Next) -> Response {
+        let mut response = next.run(request).await;
+        for (name, value) in headers() {
+            if !response.headers().contains_key(name) {
+                response.headers_mut().insert(name.clone(), value.clone());
+            }
+        }
+        response
+    }
+}
+
+// rate limiting
+
+/// Per-client token bucket rate limiting, so that a burst of traffic (or one
+/// greedy client) can't overwhelm the server. Each client address, as worked
+/// out by `access_log::client_ip`, has a separate bucket for each `Budget`;
+/// requests over budget get a 429 saying when to retry. IPv6 clients are
+/// limited by /64, as that is what a single host is usually given.
+///
+/// Health checks and /metrics aren't limited, so that busy probes and scrapers
+/// can't get a healthy server reported as down.
+mod rate_limit {
+    use std::collections::HashMap;
+    use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    use axum::extract::{ConnectInfo, Request};
+    use axum::http::{Method, StatusCode, header};
+    use axum::middleware::Next;
+    use axum::response::{IntoResponse, Response};
+    use lazy_static::lazy_static;
+
+    /// how often buckets which have refilled are forgotten
+    const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+    lazy_static! {
+        static ref LIMITER: Limiter = Limiter {
+            buckets: Mutex::new(HashMap::new()),
+            pruned: Mutex::new(Instant::now()),
+        };
+    }
+
+    /// what a request draws on, each with its own allowance
+    #[derive(strum::IntoStaticStr, PartialEq, Eq, Hash, Clone, Copy)]
+    #[strum(serialize_all = "snake_case")]
+    pub enum Budget {
+        Pages,
+        /// files under /static, several of which load with every page
+        StaticAssets,
+        /// anything but GET and HEAD, e.g. comments and logins
+        Writes,
+        /// Content-Security-Policy violation reports, which browsers send
+        /// on their own, possibly several per page
+        CspReports,
+    }
+
+    impl Budget {
+        /// `None` if the request isn't limited
+        fn for_request(request: &Request) -> Option<Self> {
+            let path = request.uri().path();
+            if path == "/health" || path.starts_with("/health/") || path == "/metrics" {
+                None
+            } else if path == super::security::CSP_REPORT_PATH {
+                Some(Budget::CspReports)
+            } else if !matches!(*request.method(), Method::GET | Method::HEAD) {
+                Some(Budget::Writes)
+            } else if path.starts_with("/static/") {
+                Some(Budget::StaticAssets)
+            } else {
+                Some(Budget::Pages)
+            }
+        }
+
+        /// (burst size, requests per second sustained)
+        fn allowance(self) -> (f64, f64) {
+            match self {
+                Budget::Pages => (60.0, 2.0),
+                Budget::StaticAssets => (200.0, 10.0),
+                Budget::Writes => (10.0, 1.0 / 6.0),
+                Budget::CspReports => (20.0, 0.5),
+            }
+        }
+    }
+
+    struct Bucket {
+        tokens: f64,
+        updated: Instant,
+    }
+
+    struct Limiter {
+        buckets: Mutex<HashMap<(Budget, IpAddr), Bucket>>,
+        pruned: Mutex<Instant>,
+    }
+
+    impl Limiter {
+        /// take a token from the client's bucket, or say how long until there
+        /// is one
+        fn take(&self, budget: Budget, client: IpAddr, now: Instant) -> Result<(), Duration> {
+            let (burst, per_second) = budget.allowance();
+            let mut buckets = self.buckets.lock().unwrap();
+
+            let mut pruned = self.pruned.lock().unwrap();
+            if now.duration_since(*pruned) > PRUNE_INTERVAL {
+                // a full bucket is the same as no bucket
+                buckets.retain(|(budget, _), bucket| {
+                    let (burst, per_second) = budget.allowance();
+                    bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * per_second
+                        < burst
+                });
+                *pruned = now;
+            }
+
+            let bucket = buckets.entry((budget, client)).or_insert(Bucket {
+                tokens: burst,
+                updated: now,
+            });
+            bucket.tokens = (bucket.tokens
+                + now.duration_since(bucket.updated).as_secs_f64() * per_second)
+                .min(burst);
+            bucket.updated = now;
+
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                Ok(())
+            } else {
+                Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+            }
+        }
+    }
+
+    /// the part of a client's address which identifies it: all of an IPv4
+    /// address, or the /64 network of an IPv6 one
+    fn client_key(client: IpAddr) -> IpAddr {
+        match client.to_canonical() {
+            IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from_bits(ip.to_bits() & (u128::MAX << 64))),
+            ip => ip,
+        }
+    }
+
+    /// whole seconds to wait, for `Retry-After`: at least 1, as 0 means now
+    fn retry_after_secs(wait: Duration) -> u64 {
+        wait.as_secs_f64().ceil().max(1.0) as u64
+    }
+
+    /// Middleware which rejects requests over their client's budget.
+    pub async fn limit(request: Request, next: Next) -> Response {
+        let Some(ConnectInfo(peer)) = request.extensions().get::<ConnectInfo<SocketAddr>>() else {
+            return next.run(request).await;
+        };
+        let Some(budget) = Budget::for_request(&request) else {
+            return next.run(request).await;
+        };
+        let client = client_key(super::client_ip(request.headers(), *peer));
+
+        match LIMITER.take(budget, client, Instant::now()) {
+            Ok(()) => next.run(request).await,
+            Err(wait) => {
+                super::metrics::throttled(budget.into());
+                tracing::debug!(%client, budget = <&str>::from(budget), "rate limited");
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after_secs(wait).to_string())],
+                    "Too many requests; please slow down.",
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn limiter(now: Instant) -> Limiter {
+            Limiter {
+                buckets: Mutex::new(HashMap::new()),
+                pruned: Mutex::new(now),
+            }
+        }
+
+        fn ip(ip: &str) -> IpAddr {
+            ip.parse().unwrap()
+        }
+
+        #[test]
+        fn allows_a_burst_then_limits() {
+            let now = Instant::now();
+            let limiter = limiter(now);
+            let client = ip("192.0.2.1");
+            for _ in 0..60 {
+                assert!(limiter.take(Budget::Pages, client, now).is_ok());
+            }
+            assert!(limiter.take(Budget::Pages, client, now).is_err());
+
+            // each budget and client has its own bucket
+            assert!(limiter.take(Budget::StaticAssets, client, now).is_ok());
+            assert!(limiter.take(Budget::Pages, ip("192.0.2.2"), now).is_ok());
+        }
+
+        #[test]
+        fn refills_at_the_sustained_rate() {
+            let start = Instant::now();
+            let limiter = limiter(start);
+            let client = ip("192.0.2.1");
+            for _ in 0..10 {
+                assert!(limiter.take(Budget::Writes, client, start).is_ok());
+            }
+
+            // one write every 6 seconds
+            let wait = limiter.take(Budget::Writes, client, start).unwrap_err();
+            assert_eq!(wait, Duration::from_secs(6));
+            let later = start + Duration::from_secs(3);
+            let wait = limiter.take(Budget::Writes, client, later).unwrap_err();
+            assert_eq!(wait, Duration::from_secs(3));
+            let later = start + Duration::from_secs(6);
+            assert!(limiter.take(Budget::Writes, client, later).is_ok());
+            assert!(limiter.take(Budget::Writes, client, later).is_err());
+
+            // but never beyond the burst size
+            let much_later = start + Duration::from_secs(60 * 60);
+            for _ in 0..10 {
+                assert!(limiter.take(Budget::Writes, client, much_later).is_ok());
+            }
+            assert!(limiter.take(Budget::Writes, client, much_later).is_err());
+        }
+
+        #[test]
+        fn rounds_retry_after_up_to_whole_seconds() {
+            assert_eq!(retry_after_secs(Duration::ZERO), 1);
+            assert_eq!(retry_after_secs(Duration::from_millis(1)), 1);
+            assert_eq!(retry_after_secs(Duration::from_millis(500)), 1);
+            assert_eq!(retry_after_secs(Duration::from_secs(6)), 6);
+            assert_eq!(retry_after_secs(Duration::from_millis(6001)), 7);
+        }
+
+        #[test]
+        fn prunes_refilled_buckets() {
+            let start = Instant::now();
+            let limiter = limiter(start);
+            // refilled by the time pruning runs
+            assert!(limiter.take(Budget::Pages, ip("192.0.2.1"), start).is_ok());
+            // still empty then
+            let late = start + Duration::from_secs(59);
+            for _ in 0..10 {
+                assert!(limiter.take(Budget::Writes, ip("192.0.2.2"), late).is_ok());
+            }
+            // not pruned before PRUNE_INTERVAL has passed
+            assert_eq!(limiter.buckets.lock().unwrap().len(), 2);
+
+            let after_interval = start + PRUNE_INTERVAL + Duration::from_secs(1);
+            assert!(
+                limiter
+                    .take(Budget::Pages, ip("192.0.2.3"), after_interval)
+                    .is_ok()
+            );
+            let buckets = limiter.buckets.lock().unwrap();
+            assert!(!buckets.contains_key(&(Budget::Pages, ip("192.0.2.1"))));
+            assert!(buckets.contains_key(&(Budget::Writes, ip("192.0.2.2"))));
+            assert!(buckets.contains_key(&(Budget::Pages, ip("192.0.2.3"))));
+        }
+
+        #[test]
+        fn keys_ipv6_clients_by_network() {
+            assert_eq!(client_key(ip("2001:db8:1:2:3:4:5:6")), ip("2001:db8:1:2::"));
+            assert_eq!(client_key(ip("2001:db8:1:2:ffff::1")), ip("2001:db8:1:2::"));
+            assert_ne!(client_key(ip("2001:db8:1:3::1")), ip("2001:db8:1:2::"));
+            assert_eq!(client_key(ip("192.0.2.1")), ip("192.0.2.1"));
+            assert_eq!(client_key(ip("::ffff:192.0.2.1")), ip("192.0.2.1"));
+        }
+    }
+}
+
+// utility functions
+
+#[tracing::instrument(skip_all, fields(bytes = markdown.len()))]
+fn markdown_to_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::all());
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+    html_output
+}
+
+/// Render markdown as readable plain text: headings are underlined, links are
+/// followed by their url, code blocks are indented, and html is dropped.
+fn markdown_to_plaintext(markdown: &str) -> String {
+    let mut out = String::new();
+    // start of the current heading in `out`, used to size its underline
+    let mut heading_start = 0;
+    // one entry per nested list; `Some(n)` is the next number of an ordered list
+    let mut lists: Vec<Option<u64>> = vec![];
+    // destination and start in `out` of each open link
+    let mut links: Vec<(String, usize)> = vec![];
+    let mut quote_depth = 0;
+    let mut in_code_block = false;
+
+    let block_end = |out: &mut String, lists: &Vec<Option<u64>>| {
+        out.push('\n');
+        if lists.is_empty() {
+            out.push('\n');
+        }
+    };
+
+    for event in Parser::new_ext(markdown, Options::all()) {
+        match event {
+            Event::Start(Tag::Paragraph) => out.push_str(&"> ".repeat(quote_depth)),
+            Event::End(TagEnd::Paragraph) => block_end(&mut out, &lists),
+            Event::Start(Tag::Heading { .. }) => heading_start = out.len(),
+            Event::End(TagEnd::Heading(level)) => {
+                let width = out[heading_start..].chars().count();
+                let underline = if level == HeadingLevel::H1 { "=" } else { "-" };
+                out.push('\n');
+                out.push_str(&underline.repeat(width));
+                out.push_str("\n\n");
+            }
+            Event::Start(Tag::BlockQuote(_)) => quote_depth += 1,
+            Event::End(TagEnd::BlockQuote(_)) => quote_depth -= 1,
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                if lists.is_empty() {
+                    out.push('\n');
+                }
+            }
+            Event::Start(Tag::List(first)) => {
+                if !lists.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                lists.push(first);
+            }
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+                if lists.is_empty() {
+                    out.push('\n');
+                }
+            }
+            Event::Start(Tag::Item) => {
+                out.push_str(&"  ".repeat(lists.len().saturating_sub(1)));
+                match lists.last_mut() {
+                    Some(Some(n)) => {
+                        out.push_str(&format!("{n}. "));
+                        *n += 1;
+                    }
+                    _ => out.push_str("- "),
+                }
+            }
+            Event::End(TagEnd::Item) if !out.ends_with('\n') => out.push('\n'),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                links.push((absolute_url(&dest_url), out.len()));
+            }
+            Event::End(TagEnd::Link) => {
+                if let Some((dest_url, start)) = links.pop()
+                    && out[start..] != dest_url
+                {
+                    out.push_str(&format!(" ({dest_url})"));
+                }
+            }
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                links.push((absolute_url(&dest_url), out.len()));
+                out.push_str("[image: ");
+            }
+            Event::End(TagEnd::Image) => {
+                if let Some((dest_url, _)) = links.pop() {
+                    out.push_str(&format!("] ({dest_url})"));
+                }
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => out.push_str(&format!("[{label}]: ")),
+            Event::FootnoteReference(label) => out.push_str(&format!("[{label}]")),
+            Event::End(TagEnd::TableCell) => out.push('\t'),
+            Event::End(TagEnd::TableHead | TagEnd::TableRow) => out.push('\n'),
+            Event::End(TagEnd::Table) => out.push('\n'),
+            Event::TaskListMarker(checked) => out.push_str(if checked { "[x] " } else { "[ ] " }),
+            Event::Text(text) if in_code_block => {
+                for line in text.lines() {
+                    out.push_str("    ");
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+            Event::Text(text)
+            | Event::Code(text)
+            | Event::InlineMath(text)
+            | Event::DisplayMath(text) => out.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => {
+                out.push('\n');
+                out.push_str(&"> ".repeat(quote_depth));
+            }
+            Event::Rule => out.push_str("----\n\n"),
+            _ => {}
+        }
+    }
+
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// resolve a site-relative url (e.g. `/static/headshot.jpg`) against `SITE_URL`
+fn absolute_url(url: &str) -> String {
+    if url.starts_with('/') {
+        format!("{SITE_URL}{url}")
+    } else {
+        url.to_string()
+    }
+}
+
+/// e.g. "2023-06 – present", if the start date is known; `unended` stands in
+/// for a missing end date
+fn formatted_date_range(
+    started: Option<NaiveDate>,
+    ended: Option<NaiveDate>,
+    unended: &str,
+) -> Option<String> {
+    let ended = match ended {
+        Some(ended) => ended.format("%Y-%m").to_string(),
+        None => unended.to_string(),
+    };
+    Some(format!("{} – {}", started?.format("%Y-%m"), ended))
+}
+
+/// find a post by id, along with its index in `POSTS`
+fn find_post(id: &str) -> Option<(usize, &'static Post)> {
+    POSTS.iter().enumerate().find(|(_, p)| p.id == id)
+}
+
+/// lowercase with runs of non-alphanumerics collapsed to `-`, so that e.g. the
+/// "MTA Display" tag and the "mta-display" project id compare equal
+fn slug(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// distinct lowercase words of a document, ignoring short (and mostly filler) words
+fn vocabulary(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() > 4)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn id(s: &str) -> String {
+    format!("#{s}")
+}
+
+/// for pages which mustn't be cached, like the admin dashboard
+fn no_store(markup: Markup) -> Response {
+    ([(header::CACHE_CONTROL, "no-store")], markup).into_response()
+}
+
+/// Counts what each client address has done recently, to limit it to `limit`
+/// times per `window`, e.g. how often each visitor may comment.
+struct RecentActivity {
+    limit: usize,
+    window: std::time::Duration,
+    recent: std::sync::Mutex<HashMap<IpAddr, Vec<std::time::Instant>>>,
+}
+
+impl RecentActivity {
+    fn new(limit: usize, window: std::time::Duration) -> Self {
+        Self {
+            limit,
+            window,
+            recent: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// record that `client` did something, unless it has reached the limit
+    fn record(&self, client: IpAddr) -> bool {
+        let mut recent = self.recent.lock().unwrap();
+        let now = std::time::Instant::now();
+        recent.retain(|_, times| {
+            times.retain(|t| now.duration_since(*t) < self.window);
+            !times.is_empty()
+        });
+        let times = recent.entry(client).or_default();
+        if times.len() >= self.limit {
+            return false;
+        }
+        times.push(now);
+        true
+    }
+}
+
+/// the address a request came from, see `access_log::client_ip`
+fn client_ip(headers: &HeaderMap, peer: SocketAddr) -> IpAddr {
+    access_log::client_ip(headers, peer)
+}
+
+/// `bytes` random bytes from the OS, in hex
+fn random_hex(bytes: usize) -> String {
+    use argon2::password_hash::rand_core::{OsRng, RngCore};
+
+    let mut buffer = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut buffer);
+    buffer.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// resolves once `shutdown_signal` has fired (or its sender is gone)
+async fn shutdown_requested(mut shutdown: watch::Receiver<()>) {
+    let _ = shutdown.changed().await;
 }
 
 async fn shutdown_signal() {
@@ -0,0 +1,151 @@
+//! Trace export over OTLP/HTTP, checked against a stand-in collector which
+//! records the exports it receives. Run it with
+//! `cargo test --features otel`.
+#![cfg(feature = "otel")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+/// the server, killed when the test ends however it ends
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// an OTLP/HTTP export request, as received by the collector
+struct Export {
+    request_line: String,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+/// accept one export on `listener` and acknowledge it
+fn receive_export(listener: &TcpListener) -> std::io::Result<Export> {
+    let (mut stream, _) = listener.accept()?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let (mut content_type, mut content_length) = (None, 0);
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            match name.to_ascii_lowercase().as_str() {
+                "content-type" => content_type = Some(value.trim().to_string()),
+                "content-length" => content_length = value.trim().parse().unwrap_or_default(),
+                _ => {}
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")?;
+    Ok(Export {
+        request_line: request_line.trim_end().to_string(),
+        content_type,
+        body,
+    })
+}
+
+/// a port which nothing is listening on, for the server
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// send a request continuing the `TRACE_ID` trace, once the server is up
+fn send_traced_request(port: u16, deadline: Instant) {
+    let mut stream = loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => break stream,
+            Err(e) if Instant::now() > deadline => panic!("server didn't start: {e}"),
+            Err(_) => thread::sleep(Duration::from_millis(100)),
+        }
+    };
+    write!(
+        stream,
+        "GET /health/live HTTP/1.1\r\nHost: localhost\r\ntraceparent: 00-{TRACE_ID}-00f067aa0ba902b7-01\r\nConnection: close\r\n\r\n"
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(
+        response.starts_with("HTTP/1.1 200"),
+        "unexpected response: {response}"
+    );
+}
+
+#[test]
+fn exports_request_spans() {
+    let collector = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", collector.local_addr().unwrap());
+    let (sender, exports) = mpsc::channel();
+    thread::spawn(move || {
+        loop {
+            let export = receive_export(&collector);
+            let failed = export.is_err();
+            if sender.send(export).is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let port = free_port();
+    let _server = Server(
+        Command::new(env!("CARGO_BIN_EXE_www"))
+            .env("PORT", port.to_string())
+            .env("OTEL_EXPORTER_OTLP_ENDPOINT", &endpoint)
+            // export as soon as possible, rather than every 5 seconds
+            .env("OTEL_BSP_SCHEDULE_DELAY", "100")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+    send_traced_request(port, Instant::now() + Duration::from_secs(10));
+
+    // the span continues the incoming trace, whose id is encoded as raw bytes
+    let trace_id = (0..TRACE_ID.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&TRACE_ID[i..i + 2], 16).unwrap())
+        .collect::<Vec<_>>();
+    // other spans, e.g. from startup, may be exported in earlier batches
+    let deadline = Instant::now() + Duration::from_secs(20);
+    loop {
+        let export = exports
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .unwrap_or_else(|_| panic!("no spans in trace {TRACE_ID} were exported"))
+            .unwrap();
+        assert!(
+            export.request_line.starts_with("POST /v1/traces "),
+            "unexpected request: {}",
+            export.request_line
+        );
+        assert_eq!(
+            export.content_type.as_deref(),
+            Some("application/x-protobuf")
+        );
+        if export.body.windows(trace_id.len()).any(|w| w == trace_id) {
+            break;
+        }
+    }
+}